@@ -1,36 +1,458 @@
-use crate::Direction;
+#[cfg(feature = "egui")]
 use eframe::egui::Vec2;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde::de::{SeqAccess, Unexpected, Visitor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, Index, IndexMut, Mul};
 
-#[derive(Debug, Serialize, Deserialize)]
+const DEFAULT_LAYER_NAME: &str = "Layer 1";
+
+pub const MAX_LEVEL: u8 = 15;
+
+pub const DEFAULT_MATRIX_SIZE: u8 = 8;
+
+const SHAKE_SEED: u64 = 0x5EED;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Frame {
+    #[cfg_attr(feature = "serde", serde(with = "packed_layers"))]
+    layers: Vec<Vec<bool>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    names: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    enabled: Vec<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    layer_levels: Vec<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    note: String,
+}
+
+impl Frame {
+    fn new(pixel_count: usize, names: Vec<String>) -> Self {
+        Self {
+            layers: vec![vec![false; pixel_count]; names.len()],
+            enabled: vec![true; names.len()],
+            layer_levels: vec![vec![MAX_LEVEL; pixel_count]; names.len()],
+            names,
+            name: String::new(),
+            note: String::new(),
+        }
+    }
+
+    fn composite(&self) -> Vec<bool> {
+        let mut composite = vec![false; self.layers[0].len()];
+        self.layers
+            .iter()
+            .zip(&self.enabled)
+            .filter(|&(_, &enabled)| enabled)
+            .for_each(|(layer, _)| {
+                composite
+                    .iter_mut()
+                    .zip(layer)
+                    .for_each(|(pixel, &layer_pixel)| *pixel |= layer_pixel);
+            });
+        composite
+    }
+
+    fn composite_levels(&self) -> Vec<u8> {
+        (0..self.layers[0].len())
+            .map(|i| self.composite_level(i))
+            .collect()
+    }
+
+    fn composite_level(&self, i: usize) -> u8 {
+        self.layers
+            .iter()
+            .zip(&self.layer_levels)
+            .zip(&self.enabled)
+            .filter(|&((layer, _), &enabled)| enabled && layer[i])
+            .map(|((_, levels), _)| levels[i])
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImageSequence {
+    #[cfg_attr(feature = "serde", serde(default))]
+    frames: Vec<Frame>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing))]
     bitmaps: Vec<Vec<bool>>,
     width: u8,
     height: u8,
+    #[cfg_attr(feature = "serde", serde(default))]
+    matrix_size: u8,
+    #[cfg_attr(feature = "serde", serde(default))]
+    frame_durations: Vec<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    background_colors: Vec<Option<[u8; 3]>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    keyframes: Vec<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    locked_frames: BTreeSet<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    active_layer: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    mode: CanvasMode,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    discarded_pixel: bool,
 }
 
 impl ImageSequence {
     pub fn new(width: u8, height: u8) -> Self {
+        Self::with_matrix_size(width, height, DEFAULT_MATRIX_SIZE)
+    }
+
+    pub fn with_matrix_size(width: u8, height: u8, matrix_size: u8) -> Self {
+        let matrix_size = matrix_size.max(1);
         Self {
-            bitmaps: vec![vec![
-                false;
-                usize::from(width) * 8 * usize::from(height) * 8
-            ]],
+            frames: vec![Frame::new(
+                usize::from(width)
+                    * usize::from(matrix_size)
+                    * usize::from(height)
+                    * usize::from(matrix_size),
+                vec![String::from(DEFAULT_LAYER_NAME)],
+            )],
+            bitmaps: Vec::new(),
             width,
             height,
+            matrix_size,
+            frame_durations: vec![0],
+            background_colors: vec![None],
+            keyframes: Vec::new(),
+            locked_frames: BTreeSet::new(),
+            active_layer: 0,
+            mode: CanvasMode::Monochrome,
+            discarded_pixel: false,
+        }
+    }
+
+    pub fn from_xbm(contents: &str) -> Option<Self> {
+        let define = |suffix: &str| {
+            contents.lines().find_map(|line| {
+                let mut tokens = line.split_whitespace();
+                if tokens.next()? != "#define" || !tokens.next()?.ends_with(suffix) {
+                    return None;
+                }
+                tokens.next()?.parse().ok()
+            })
+        };
+        let width: usize = define("_width")?;
+        let height: usize = define("_height")?;
+        let (_, data) = contents.split_once('{')?;
+        let (data, _) = data.split_once('}')?;
+        let bytes = data
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                u8::from_str_radix(token.trim_start_matches("0x").trim_start_matches("0X"), 16).ok()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Self::from_xbm_bytes(&bytes, width, height)
+    }
+
+    pub fn from_xbm_bytes(bytes: &[u8], width: usize, height: usize) -> Option<Self> {
+        if width == 0 || height == 0 || bytes.len() < width.div_ceil(8) * height {
+            return None;
+        }
+        let mut image_sequence = Self::new(
+            u8::try_from(width.div_ceil(8)).unwrap_or(u8::MAX),
+            u8::try_from(height.div_ceil(8)).unwrap_or(u8::MAX),
+        );
+        let [canvas_width, canvas_height] = image_sequence.get_dimensions_pixels();
+        let row_bytes = width.div_ceil(8);
+        (0..height.min(canvas_height)).for_each(|y| {
+            (0..width.min(canvas_width)).for_each(|x| {
+                image_sequence[[x, y, 0]] = (bytes[y * row_bytes + x / 8] >> (x % 8)) & 1 == 1;
+            });
+        });
+        Some(image_sequence)
+    }
+
+    pub fn get_frame_as_xbm_bytes(&self, idx: usize) -> Vec<u8> {
+        let [width, _] = self.get_dimensions_pixels();
+        self.composite_frame(idx)
+            .chunks_exact(width)
+            .flat_map(|row| row.chunks(8))
+            .map(|bits| bits_to_byte(&bits.iter().rev().copied().collect::<Vec<_>>()))
+            .collect()
+    }
+
+    pub fn get_frame_as_bmp(&self, idx: usize) -> Vec<u8> {
+        const HEADERS_SIZE: u32 = 14 + 40 + 2 * 4;
+        let [width, height] = self.get_dimensions_pixels();
+        let row_size = width.div_ceil(32) * 4;
+        let image_size = u32::try_from(row_size * height).unwrap();
+        let mut bytes = Vec::with_capacity(usize::try_from(HEADERS_SIZE + image_size).unwrap());
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(HEADERS_SIZE + image_size).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&HEADERS_SIZE.to_le_bytes());
+        bytes.extend_from_slice(&40u32.to_le_bytes());
+        bytes.extend_from_slice(&i32::try_from(width).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&i32::try_from(height).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&image_size.to_le_bytes());
+        bytes.extend_from_slice(&[0; 16]);
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x00]);
+        let mut bitmap = self.composite_frame(idx);
+        flip_vertical_bitmap(&mut bitmap, width);
+        bitmap.chunks_exact(width).for_each(|row| {
+            let mut packed_row = pack_bits(row);
+            packed_row.resize(row_size, 0);
+            bytes.extend_from_slice(&packed_row);
+        });
+        bytes
+    }
+
+    pub fn subrange(&self, start: usize, end: usize) -> Self {
+        Self {
+            frames: self.frames[start..=end].to_vec(),
+            bitmaps: Vec::new(),
+            width: self.width,
+            height: self.height,
+            matrix_size: self.matrix_size,
+            frame_durations: self.frame_durations[start..=end].to_vec(),
+            background_colors: self.background_colors[start..=end].to_vec(),
+            keyframes: self
+                .keyframes
+                .iter()
+                .filter(|&keyframe| (start..=end).contains(keyframe))
+                .map(|&keyframe| keyframe - start)
+                .collect(),
+            locked_frames: self
+                .locked_frames
+                .range(start..=end)
+                .map(|&locked| locked - start)
+                .collect(),
+            active_layer: self.active_layer,
+            mode: self.mode,
+            discarded_pixel: false,
+        }
+    }
+
+    pub fn fill_missing_frame_data(&mut self) {
+        if self.matrix_size == 0 {
+            self.matrix_size = DEFAULT_MATRIX_SIZE;
+        }
+        if self.frames.is_empty() {
+            self.frames = self
+                .bitmaps
+                .drain(..)
+                .map(|bitmap| Frame {
+                    layers: vec![bitmap],
+                    names: vec![String::from(DEFAULT_LAYER_NAME)],
+                    enabled: vec![true],
+                    name: String::new(),
+                    layer_levels: Vec::new(),
+                    note: String::new(),
+                })
+                .collect();
+        }
+        if self.frames.is_empty() {
+            self.frames.push(self.blank_frame());
         }
+        let pixel_count = self.pixel_count();
+        let layer_count = self
+            .frames
+            .iter()
+            .map(|frame| frame.layers.len())
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        self.frames.iter_mut().for_each(|frame| {
+            frame.layers.resize(layer_count, Vec::new());
+            frame
+                .layers
+                .iter_mut()
+                .for_each(|layer| layer.resize(pixel_count, false));
+            let named_layers = frame.names.len();
+            frame
+                .names
+                .extend((named_layers..layer_count).map(|layer| format!("Layer {}", layer + 1)));
+            frame.enabled.resize(layer_count, true);
+            frame.layer_levels.resize(layer_count, Vec::new());
+            frame
+                .layer_levels
+                .iter_mut()
+                .for_each(|levels| levels.resize(pixel_count, MAX_LEVEL));
+        });
+        self.frame_durations.resize(self.frames.len(), 0);
+        self.background_colors.resize(self.frames.len(), None);
+        let frame_count = self.frames.len();
+        self.keyframes.retain(|&keyframe| keyframe < frame_count);
+        self.locked_frames.retain(|&locked| locked < frame_count);
+        self.active_layer = self.active_layer.min(self.get_layer_count() - 1);
     }
 
     pub fn get_frame_count(&self) -> usize {
-        self.bitmaps.len()
+        self.frames.len()
+    }
+
+    pub fn get_layer_count(&self) -> usize {
+        self.frames[0].layers.len()
+    }
+
+    pub fn get_layer_name(&self, layer: usize) -> &str {
+        &self.frames[0].names[layer]
+    }
+
+    pub fn get_active_layer(&self) -> usize {
+        self.active_layer
+    }
+
+    pub fn set_active_layer(&mut self, layer: usize) {
+        self.active_layer = layer.min(self.get_layer_count() - 1);
+    }
+
+    pub fn is_layer_enabled(&self, idx: usize, layer: usize) -> bool {
+        self.frames[idx].enabled[layer]
+    }
+
+    pub fn toggle_layer(&mut self, idx: usize, layer: usize) {
+        self.frames[idx].enabled[layer] ^= true;
+    }
+
+    pub fn add_layer(&mut self) {
+        let name = format!("Layer {}", self.get_layer_count() + 1);
+        let pixel_count = self.pixel_count();
+        self.frames.iter_mut().for_each(|frame| {
+            frame.layers.push(vec![false; pixel_count]);
+            frame.names.push(name.clone());
+            frame.enabled.push(true);
+            frame.layer_levels.push(vec![MAX_LEVEL; pixel_count]);
+        });
+        self.active_layer = self.get_layer_count() - 1;
+    }
+
+    pub fn delete_layer(&mut self, layer: usize) -> bool {
+        if self.get_layer_count() == 1 {
+            return false;
+        }
+        self.frames.iter_mut().for_each(|frame| {
+            frame.layers.remove(layer);
+            frame.names.remove(layer);
+            frame.enabled.remove(layer);
+            frame.layer_levels.remove(layer);
+        });
+        self.active_layer = self.active_layer.min(self.get_layer_count() - 1);
+        true
+    }
+
+    pub fn rename_layer(&mut self, layer: usize, name: &str) {
+        self.frames
+            .iter_mut()
+            .for_each(|frame| frame.names[layer] = name.to_owned());
+    }
+
+    pub fn move_layer_up(&mut self, layer: usize) -> bool {
+        if layer != 0 {
+            self.swap_layers(layer, layer - 1);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn move_layer_down(&mut self, layer: usize) -> bool {
+        if layer != self.get_layer_count() - 1 {
+            self.swap_layers(layer, layer + 1);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn swap_layers(&mut self, a: usize, b: usize) {
+        self.frames.iter_mut().for_each(|frame| {
+            frame.layers.swap(a, b);
+            frame.names.swap(a, b);
+            frame.enabled.swap(a, b);
+            frame.layer_levels.swap(a, b);
+        });
+        if self.active_layer == a {
+            self.active_layer = b;
+        } else if self.active_layer == b {
+            self.active_layer = a;
+        }
+    }
+
+    fn pixel_count(&self) -> usize {
+        let [width, height] = self.get_dimensions_pixels();
+        width * height
+    }
+
+    fn blank_frame(&self) -> Frame {
+        Frame::new(
+            self.pixel_count(),
+            self.frames.first().map_or_else(
+                || vec![String::from(DEFAULT_LAYER_NAME)],
+                |frame| frame.names.clone(),
+            ),
+        )
+    }
+
+    fn active_layer_mut(&mut self, idx: usize) -> &mut Vec<bool> {
+        &mut self.frames[idx].layers[self.active_layer]
+    }
+
+    fn active_levels_mut(&mut self, idx: usize) -> &mut Vec<u8> {
+        &mut self.frames[idx].layer_levels[self.active_layer]
+    }
+
+    pub fn get_mode(&self) -> CanvasMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: CanvasMode) {
+        self.mode = mode;
+    }
+
+    pub fn get_level(&self, x: usize, y: usize, idx: usize) -> Option<u8> {
+        let [width_pixels, _] = self.get_dimensions_pixels();
+        self.get_composite(x, y, idx)?;
+        Some(self.frames[idx].composite_level(y * width_pixels + x))
+    }
+
+    pub fn set_level(&mut self, x: usize, y: usize, idx: usize, level: u8) {
+        if self.is_locked(idx) {
+            return;
+        }
+        let [width_pixels, _] = self.get_dimensions_pixels();
+        let level = level.min(MAX_LEVEL);
+        if let Some(pixel) = self.get_mut(x, y, idx) {
+            *pixel = level != 0;
+            self.active_levels_mut(idx)[y * width_pixels + x] = level;
+        }
+    }
+
+    pub fn get_dimensions_matrices(&self) -> [u8; 2] {
+        [self.width, self.height]
+    }
+
+    pub fn get_matrix_size(&self) -> u8 {
+        self.matrix_size
     }
 
     pub fn get_dimensions_pixels(&self) -> [usize; 2] {
-        [usize::from(self.width) * 8, usize::from(self.height) * 8]
+        let matrix_size = usize::from(self.matrix_size);
+        [
+            usize::from(self.width) * matrix_size,
+            usize::from(self.height) * matrix_size,
+        ]
     }
 
+    #[cfg(feature = "egui")]
     pub fn get_dimensions_pixels_vec2(&self) -> Vec2 {
         let [width, height] = self.get_dimensions_pixels();
         Vec2::new(width as f32, height as f32)
@@ -39,75 +461,329 @@ impl ImageSequence {
     pub fn get(&self, x: usize, y: usize, idx: usize) -> Option<&bool> {
         let [width_pixels, height_pixels] = self.get_dimensions_pixels();
         if (0..width_pixels).contains(&x) && (0..height_pixels).contains(&y) {
-            self.bitmaps.get(idx)?.get(y * width_pixels + x)
+            self.frames.get(idx)?.layers[self.active_layer].get(y * width_pixels + x)
         } else {
             None
         }
     }
 
-    pub fn get_mut(&mut self, x: usize, y: usize, idx: usize) -> Option<&mut bool> {
+    pub fn get_composite(&self, x: usize, y: usize, idx: usize) -> Option<bool> {
         let [width_pixels, height_pixels] = self.get_dimensions_pixels();
         if (0..width_pixels).contains(&x) && (0..height_pixels).contains(&y) {
-            self.bitmaps.get_mut(idx)?.get_mut(y * width_pixels + x)
+            let frame = self.frames.get(idx)?;
+            Some(
+                frame
+                    .layers
+                    .iter()
+                    .zip(&frame.enabled)
+                    .any(|(layer, &enabled)| enabled && layer[y * width_pixels + x]),
+            )
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize, idx: usize) -> Option<&mut bool> {
+        let [width_pixels, height_pixels] = self.get_dimensions_pixels();
+        if (0..width_pixels).contains(&x) && (0..height_pixels).contains(&y) && !self.is_locked(idx)
+        {
+            self.frames.get_mut(idx)?.layers[self.active_layer].get_mut(y * width_pixels + x)
         } else {
             None
         }
     }
 
-    pub fn get_frame(&self, idx: usize) -> Option<&[bool]> {
-        self.bitmaps.get(idx).map(|vec| vec.as_ref())
+    pub fn set(&mut self, x: usize, y: usize, idx: usize, value: bool) -> Result<(), FrameError> {
+        if idx >= self.frames.len() {
+            return Err(FrameError::OutOfRange(idx));
+        }
+        if self.is_locked(idx) {
+            return Err(FrameError::Locked(idx));
+        }
+        let pixel = self
+            .get_mut(x, y, idx)
+            .ok_or(FrameError::PixelOutOfRange(x, y))?;
+        *pixel = value;
+        Ok(())
+    }
+
+    pub fn composite_frame(&self, idx: usize) -> Vec<bool> {
+        self.frames[idx].composite()
+    }
+
+    pub fn composite_frame_levels(&self, idx: usize) -> Vec<u8> {
+        self.frame_levels(&self.frames[idx])
+    }
+
+    fn frame_levels(&self, frame: &Frame) -> Vec<u8> {
+        match self.mode {
+            CanvasMode::Monochrome => frame
+                .composite()
+                .iter()
+                .map(|&pixel| if pixel { MAX_LEVEL } else { 0 })
+                .collect(),
+            CanvasMode::Grayscale4Bit => frame.composite_levels(),
+        }
+    }
+
+    pub fn get_active_layer_mut(&mut self, idx: usize) -> Option<&mut [bool]> {
+        if self.is_locked(idx) {
+            return None;
+        }
+        let active_layer = self.active_layer;
+        self.frames
+            .get_mut(idx)
+            .map(|frame| &mut frame.layers[active_layer][..])
     }
 
-    pub fn get_frame_mut(&mut self, idx: usize) -> Option<&mut [bool]> {
-        self.bitmaps.get_mut(idx).map(|vec| &mut vec[..])
+    pub fn get_active_levels_mut(&mut self, idx: usize) -> Option<&mut [u8]> {
+        if self.is_locked(idx) {
+            return None;
+        }
+        let active_layer = self.active_layer;
+        self.frames
+            .get_mut(idx)
+            .map(|frame| &mut frame.layer_levels[active_layer][..])
     }
 
     pub fn iter_pixels(
         &self,
         idx: usize,
     ) -> Option<impl Iterator<Item = (usize, usize, bool)> + '_> {
-        Some(self.get_frame(idx)?.iter().enumerate().map(|(i, &pixel)| {
-            let width = usize::from(self.width) * 8;
-            (i % width, i / width, pixel)
-        }))
+        let [width, _] = self.get_dimensions_pixels();
+        Some(
+            self.frames
+                .get(idx)?
+                .composite()
+                .into_iter()
+                .enumerate()
+                .map(move |(i, pixel)| (i % width, i / width, pixel)),
+        )
     }
 
     pub fn iter_pixels_mut(&mut self, idx: usize) -> Option<impl Iterator<Item = &mut bool>> {
-        Some(self.get_frame_mut(idx)?.iter_mut())
+        Some(self.get_active_layer_mut(idx)?.iter_mut())
+    }
+
+    pub fn iter_frames(&self) -> impl Iterator<Item = Vec<bool>> + '_ {
+        self.frames.iter().map(Frame::composite)
+    }
+
+    pub fn iter_frame_levels(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        self.frames.iter().map(|frame| self.frame_levels(frame))
+    }
+
+    pub fn get_frame_name(&self, idx: usize) -> Option<&str> {
+        self.frames.get(idx).map(|frame| frame.name.as_str())
+    }
+
+    pub fn get_frame_name_mut(&mut self, idx: usize) -> Option<&mut String> {
+        self.frames.get_mut(idx).map(|frame| &mut frame.name)
+    }
+
+    pub fn get_frame_note_mut(&mut self, idx: usize) -> Option<&mut String> {
+        self.frames.get_mut(idx).map(|frame| &mut frame.note)
+    }
+
+    pub fn get_frame_duration(&self, idx: usize) -> Option<u32> {
+        self.frame_durations.get(idx).copied()
+    }
+
+    pub fn get_frame_duration_mut(&mut self, idx: usize) -> Option<&mut u32> {
+        self.frame_durations.get_mut(idx)
+    }
+
+    pub fn get_background_color(&self, idx: usize) -> Option<[u8; 3]> {
+        self.background_colors.get(idx).copied().flatten()
+    }
+
+    pub fn get_background_color_mut(&mut self, idx: usize) -> Option<&mut Option<[u8; 3]>> {
+        self.background_colors.get_mut(idx)
+    }
+
+    pub fn get_background_colors_as_c(&self, var_name: &str, default: [u8; 3]) -> Option<String> {
+        if self.background_colors.iter().all(Option::is_none) {
+            return None;
+        }
+        Some(format!(
+            "const uint8_t {var_name}_backgrounds[{}][3] = {{{}}};",
+            self.background_colors.len(),
+            self.background_colors
+                .iter()
+                .map(|color| {
+                    let [r, g, b] = color.unwrap_or(default);
+                    format!("{{{r:#04X}, {g:#04X}, {b:#04X}}}")
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
+    pub fn iter_frame_durations_ms(&self, frame_rate: u16) -> impl Iterator<Item = u32> + '_ {
+        self.frame_durations.iter().map(move |&duration| {
+            if duration == 0 {
+                1000 / u32::from(frame_rate)
+            } else {
+                duration
+            }
+        })
+    }
+
+    pub fn total_duration_ms(&self, frame_rate: u16) -> u32 {
+        self.iter_frame_durations_ms(frame_rate).sum()
+    }
+
+    pub fn frame_stats(&self, idx: usize) -> FrameStats {
+        let bitmap = self.composite_frame(idx);
+        FrameStats {
+            lit: bitmap.iter().filter(|&&pixel| pixel).count(),
+            total: bitmap.len(),
+        }
+    }
+
+    pub fn hamming_distance(&self, a: usize, b: usize) -> usize {
+        self.frames[a]
+            .composite()
+            .iter()
+            .zip(self.frames[b].composite())
+            .filter(|&(&pixel_a, pixel_b)| pixel_a != pixel_b)
+            .count()
+    }
+
+    pub fn pixel_frequency(&self) -> Vec<f32> {
+        let frame_count = self.frames.len() as f32;
+        let mut frequency = vec![0.0; self.pixel_count()];
+        self.iter_frames().for_each(|bitmap| {
+            frequency
+                .iter_mut()
+                .zip(&bitmap)
+                .filter(|&(_, &pixel)| pixel)
+                .for_each(|(count, _)| *count += 1.0)
+        });
+        frequency.iter_mut().for_each(|count| *count /= frame_count);
+        frequency
+    }
+
+    pub fn get_changed_bounds(&self, previous_idx: usize, idx: usize) -> Option<[usize; 4]> {
+        let [width, _] = self.get_dimensions_pixels();
+        self.composite_frame_levels(previous_idx)
+            .iter()
+            .zip(&self.composite_frame_levels(idx))
+            .enumerate()
+            .filter(|(_, (previous, current))| previous != current)
+            .map(|(i, _)| (i % width, i / width))
+            .fold(None, |bounds, (x, y)| {
+                Some(match bounds {
+                    None => [x, y, x, y],
+                    Some([min_x, min_y, max_x, max_y]) => {
+                        [min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)]
+                    }
+                })
+            })
+    }
+
+    pub fn find_pattern(
+        &self,
+        pattern: &[bool],
+        pattern_width: usize,
+        pattern_height: usize,
+    ) -> Vec<(usize, usize, usize)> {
+        let [width, height] = self.get_dimensions_pixels();
+        if pattern_width == 0
+            || pattern_height == 0
+            || pattern_width > width
+            || pattern_height > height
+        {
+            return Vec::new();
+        }
+        self.iter_frames()
+            .enumerate()
+            .flat_map(|(idx, bitmap)| {
+                (0..=height - pattern_height)
+                    .flat_map(move |y| (0..=width - pattern_width).map(move |x| (idx, x, y)))
+                    .filter(move |&(_, x, y)| {
+                        (0..pattern_height).all(|pattern_y| {
+                            (0..pattern_width).all(|pattern_x| {
+                                bitmap[(y + pattern_y) * width + x + pattern_x]
+                                    == pattern[pattern_y * pattern_width + pattern_x]
+                            })
+                        })
+                    })
+            })
+            .collect()
     }
 
-    pub fn iter_frames(&self) -> impl Iterator<Item = &[bool]> {
-        self.bitmaps.iter().map(|vector| vector.as_ref())
+    pub fn get_bytes(&self, idx: usize) -> impl Iterator<Item = u8> {
+        self.frame_bytes(&self.frames[idx]).into_iter()
     }
 
-    pub fn get_bytes(&self, idx: usize) -> impl Iterator<Item = u8> + '_ {
-        self.bitmaps[idx].chunks_exact(8).map(bits_to_byte)
+    pub fn get_bytes_with_offset(&self, idx: usize, dx: i8, dy: i8) -> impl Iterator<Item = u8> {
+        self.frame_bytes(&self.shifted_frame(&self.frames[idx], dx, dy))
+            .into_iter()
+    }
+
+    pub fn with_output_offset(&self, dx: i8, dy: i8) -> Self {
+        let mut image_sequence = self.clone();
+        image_sequence.frames = self
+            .frames
+            .iter()
+            .map(|frame| self.shifted_frame(frame, dx, dy))
+            .collect();
+        image_sequence
+    }
+
+    fn frame_bytes(&self, frame: &Frame) -> Vec<u8> {
+        match self.mode {
+            CanvasMode::Monochrome => pack_bits(&frame.composite()),
+            CanvasMode::Grayscale4Bit => pack_nibbles(&self.frame_levels(frame)),
+        }
+    }
+
+    fn shifted_frame(&self, frame: &Frame, dx: i8, dy: i8) -> Frame {
+        let [width, _] = self.get_dimensions_pixels();
+        let mut frame = frame.clone();
+        frame
+            .layers
+            .iter_mut()
+            .for_each(|layer| *layer = shift_bitmap(layer, width, dx, dy));
+        frame
+            .layer_levels
+            .iter_mut()
+            .for_each(|levels| *levels = shift_bitmap(levels, width, dx, dy));
+        frame
     }
 
     pub fn add_frame(&mut self) {
-        self.bitmaps.push(vec![
-            false;
-            usize::from(self.width)
-                * 8
-                * usize::from(self.height)
-                * 8
-        ]);
+        self.frames.push(self.blank_frame());
+        self.frame_durations.push(0);
+        self.background_colors.push(None);
     }
 
     pub fn insert_frame(&mut self, idx: usize) {
-        self.bitmaps.insert(
-            idx,
-            vec![false; usize::from(self.width) * 8 * usize::from(self.height) * 8],
-        );
+        self.frames.insert(idx, self.blank_frame());
+        self.frame_durations.insert(idx, 0);
+        self.background_colors.insert(idx, None);
+        self.shift_keyframes_after_insert(idx);
+        self.shift_locked_frames_after_insert(idx);
     }
 
     pub fn duplicate_frame(&mut self, idx: usize) {
-        self.bitmaps.insert(idx + 1, self.bitmaps[idx].clone());
+        self.frames.insert(idx + 1, self.frames[idx].clone());
+        self.frame_durations
+            .insert(idx + 1, self.frame_durations[idx]);
+        self.background_colors
+            .insert(idx + 1, self.background_colors[idx]);
+        self.shift_keyframes_after_insert(idx + 1);
+        self.shift_locked_frames_after_insert(idx + 1);
     }
 
     pub fn move_up(&mut self, idx: usize) -> bool {
         if idx != 0 {
-            self.bitmaps.swap(idx, idx - 1);
+            self.frames.swap(idx, idx - 1);
+            self.frame_durations.swap(idx, idx - 1);
+            self.background_colors.swap(idx, idx - 1);
+            self.swap_keyframes(idx, idx - 1);
+            self.swap_locked_frames(idx, idx - 1);
             true
         } else {
             false
@@ -115,154 +791,1004 @@ impl ImageSequence {
     }
 
     pub fn move_down(&mut self, idx: usize) -> bool {
-        if idx != self.bitmaps.len() - 1 {
-            self.bitmaps.swap(idx, idx + 1);
+        if idx != self.frames.len() - 1 {
+            self.frames.swap(idx, idx + 1);
+            self.frame_durations.swap(idx, idx + 1);
+            self.background_colors.swap(idx, idx + 1);
+            self.swap_keyframes(idx, idx + 1);
+            self.swap_locked_frames(idx, idx + 1);
             true
         } else {
             false
         }
     }
 
-    pub fn delete_frame(&mut self, idx: usize) {
-        self.bitmaps.remove(idx);
+    pub fn delete_frame(&mut self, idx: usize) -> Result<(), FrameError> {
+        if idx >= self.frames.len() {
+            return Err(FrameError::OutOfRange(idx));
+        }
+        if self.frames.len() == 1 {
+            return Err(FrameError::LastFrame);
+        }
+        if self.is_locked(idx) {
+            return Err(FrameError::Locked(idx));
+        }
+        self.remove_frame(idx);
+        Ok(())
     }
 
-    pub fn clear_frame(&mut self, idx: usize) {
-        self.bitmaps[idx]
+    fn remove_frame(&mut self, idx: usize) {
+        self.frames.remove(idx);
+        self.frame_durations.remove(idx);
+        self.background_colors.remove(idx);
+        self.keyframes.retain(|&keyframe| keyframe != idx);
+        self.keyframes
             .iter_mut()
-            .for_each(|pixel| *pixel = false);
+            .filter(|keyframe| **keyframe > idx)
+            .for_each(|keyframe| *keyframe -= 1);
+        self.locked_frames = self
+            .locked_frames
+            .iter()
+            .filter(|&&locked| locked != idx)
+            .map(|&locked| if locked > idx { locked - 1 } else { locked })
+            .collect();
     }
 
-    pub fn get_frame_as_string(&self, idx: usize) -> String {
-        let mut first = true;
-        format!(
-            "{{{}}}",
-            self.get_bytes(idx)
-                .fold(String::default(), |previous, current| {
-                    if first {
-                        first = false;
-                        format!("{current:#04X}")
-                    } else {
-                        format!("{previous}, {current:#04X}")
-                    }
-                })
-        )
+    pub fn count_leading_blank(&self) -> usize {
+        (0..self.frames.len())
+            .take_while(|&idx| self.is_blank_frame(idx))
+            .count()
     }
 
-    pub fn get_sequence_as_string(&self) -> String {
-        let mut first = true;
-        format!(
-            "{{{}}}",
-            self.bitmaps
-                .iter()
-                .enumerate()
-                .map(|(i, _)| self.get_frame_as_string(i))
-                .fold(String::default(), |mut previous, current| {
-                    if first {
-                        first = false;
-                        current
-                    } else {
-                        previous += ", ";
-                        previous += &current;
-                        previous
-                    }
-                })
-        )
+    pub fn count_trailing_blank(&self) -> usize {
+        (0..self.frames.len())
+            .rev()
+            .take_while(|&idx| self.is_blank_frame(idx))
+            .count()
     }
 
-    pub fn slide(&mut self, idx: usize, direction: Direction, animation: SlideAnimation) {
-        let dimension = match direction {
-            Direction::Top | Direction::Bottom => self.height,
-            Direction::Left | Direction::Right => self.width,
-        } * 8;
-
-        let vector = match direction {
-            Direction::Top => IVec::new(0, -1),
-            Direction::Left => IVec::new(-1, 0),
-            Direction::Bottom => IVec::new(0, 1),
-            Direction::Right => IVec::new(1, 0),
-        };
-
-        (0..dimension - 1).for_each(|_| self.duplicate_frame(idx));
-
-        let [width, height] = [i16::from(self.width) * 8, i16::from(self.height) * 8];
-        let current_frame = self.get_frame(idx).unwrap().to_owned();
-        (0..dimension).rev().for_each(|i| {
-            let scaled_vector = vector
-                * match animation {
-                    SlideAnimation::SlideIn => i16::from(dimension) - i16::from(i) - 1,
-                    SlideAnimation::SlideOut => i16::from(i),
-                };
-            let frame_number = idx + usize::from(i);
-            self.clear_frame(frame_number);
-            (0..width * height)
-                .map(|i| IVec::new(i % width, i / width))
-                .for_each(|current_pixel| {
-                    let IVec { x: new_x, y: new_y } = current_pixel + scaled_vector;
-                    if (0..width).contains(&new_x) && (0..height).contains(&new_y) {
-                        self[[
-                            new_x.try_into().unwrap(),
-                            new_y.try_into().unwrap(),
-                            frame_number,
-                        ]] = current_frame
-                            [usize::try_from(current_pixel.y * width + current_pixel.x).unwrap()];
-                    }
-                });
-        });
+    pub fn trim_blank_frames(&mut self) -> usize {
+        let frame_count = self.frames.len();
+        let leading = self.count_leading_blank().min(frame_count - 1);
+        let trailing = self.count_trailing_blank().min(frame_count - leading - 1);
+        (frame_count - trailing..frame_count)
+            .rev()
+            .for_each(|idx| self.remove_frame(idx));
+        (0..leading).rev().for_each(|idx| self.remove_frame(idx));
+        leading + trailing
     }
-}
 
-impl Index<[usize; 3]> for ImageSequence {
-    type Output = bool;
-
-    fn index(&self, index: [usize; 3]) -> &Self::Output {
-        self.get(index[0], index[1], index[2]).unwrap()
+    fn is_blank_frame(&self, idx: usize) -> bool {
+        !self.is_locked(idx) && !self.frames[idx].composite().contains(&true)
     }
-}
 
-impl IndexMut<[usize; 3]> for ImageSequence {
-    fn index_mut(&mut self, index: [usize; 3]) -> &mut Self::Output {
-        self.get_mut(index[0], index[1], index[2]).unwrap()
+    pub fn is_keyframe(&self, idx: usize) -> bool {
+        self.keyframes.contains(&idx)
     }
-}
 
-fn bits_to_byte(bits: &[bool]) -> u8 {
-    bits.iter().fold(0, |byte, &bit| byte << 1 | bit as u8)
-}
+    pub fn toggle_keyframe(&mut self, idx: usize) {
+        if let Some(position) = self.keyframes.iter().position(|&keyframe| keyframe == idx) {
+            self.keyframes.remove(position);
+        } else {
+            self.keyframes.push(idx);
+            self.keyframes.sort_unstable();
+        }
+    }
 
-#[derive(Clone, Copy)]
-struct IVec {
-    x: i16,
-    y: i16,
-}
+    fn shift_keyframes_after_insert(&mut self, idx: usize) {
+        self.keyframes
+            .iter_mut()
+            .filter(|keyframe| **keyframe >= idx)
+            .for_each(|keyframe| *keyframe += 1);
+    }
 
-impl IVec {
-    fn new(x: i16, y: i16) -> Self {
-        Self { x, y }
+    fn swap_keyframes(&mut self, a: usize, b: usize) {
+        self.keyframes.iter_mut().for_each(|keyframe| {
+            if *keyframe == a {
+                *keyframe = b;
+            } else if *keyframe == b {
+                *keyframe = a;
+            }
+        });
+        self.keyframes.sort_unstable();
     }
-}
 
-impl Mul<i16> for IVec {
-    type Output = IVec;
+    pub fn is_locked(&self, idx: usize) -> bool {
+        self.locked_frames.contains(&idx)
+    }
 
-    fn mul(self, rhs: i16) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
+    pub fn toggle_lock(&mut self, idx: usize) {
+        if !self.locked_frames.remove(&idx) {
+            self.locked_frames.insert(idx);
         }
     }
-}
 
-impl Add<IVec> for IVec {
-    type Output = Self;
+    fn shift_locked_frames_after_insert(&mut self, idx: usize) {
+        self.locked_frames = self
+            .locked_frames
+            .iter()
+            .map(|&locked| if locked >= idx { locked + 1 } else { locked })
+            .collect();
+    }
 
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
+    fn swap_locked_frames(&mut self, a: usize, b: usize) {
+        match (self.is_locked(a), self.is_locked(b)) {
+            (true, false) => {
+                self.locked_frames.remove(&a);
+                self.locked_frames.insert(b);
+            }
+            (false, true) => {
+                self.locked_frames.remove(&b);
+                self.locked_frames.insert(a);
+            }
+            _ => {}
         }
     }
-}
+
+    pub fn generate_tweens(&mut self, method: TweenMethod) {
+        let [width, _] = self.get_dimensions_pixels();
+        let keyframes = self.keyframes.clone();
+        keyframes.windows(2).rev().for_each(|pair| {
+            let [start, end] = [pair[0], pair[1]];
+            if self.locked_frames.range(start + 1..end).next().is_some() {
+                return;
+            }
+            let start_frame = self.frames[start].clone();
+            let end_frame = self.frames[end].clone();
+            (start + 1..end)
+                .rev()
+                .for_each(|idx| self.remove_frame(idx));
+            (1..=method.steps).rev().for_each(|step| {
+                let t = step as f32 / (method.steps + 1) as f32;
+                self.insert_frame(start + 1);
+                self.frames[start + 1].layers = start_frame
+                    .layers
+                    .iter()
+                    .zip(&end_frame.layers)
+                    .map(|(start_bitmap, end_bitmap)| {
+                        start_bitmap
+                            .iter()
+                            .zip(end_bitmap)
+                            .enumerate()
+                            .map(|(i, (&from, &to))| {
+                                let value = match (from, to) {
+                                    (false, false) => 0.0,
+                                    (true, true) => 1.0,
+                                    (false, true) => t,
+                                    (true, false) => 1.0 - t,
+                                };
+                                method.dither.apply(value, i % width, i / width)
+                            })
+                            .collect()
+                    })
+                    .collect();
+            });
+        });
+    }
+
+    pub fn vu_meter_animation(&mut self, levels: &[f32], horizontal: bool) {
+        let [width, height] = self.get_dimensions_pixels();
+        levels.iter().for_each(|&level| {
+            self.add_frame();
+            let level = level.clamp(0.0, 1.0);
+            let bitmap = self.active_layer_mut(self.frames.len() - 1);
+            bitmap.iter_mut().enumerate().for_each(|(i, pixel)| {
+                *pixel = if horizontal {
+                    ((i % width) as f32) < level * width as f32
+                } else {
+                    ((height - 1 - i / width) as f32) < level * height as f32
+                };
+            });
+        });
+    }
+
+    pub fn clear_frame(&mut self, idx: usize) {
+        if self.is_locked(idx) {
+            return;
+        }
+        self.active_layer_mut(idx)
+            .iter_mut()
+            .for_each(|pixel| *pixel = false);
+    }
+
+    pub fn test_pattern_all_on(&mut self, frame_idx: usize) {
+        self.fill_pattern(frame_idx, |_, _| true);
+    }
+
+    pub fn test_pattern_border(&mut self, frame_idx: usize) {
+        let [width, height] = self.get_dimensions_pixels();
+        self.fill_pattern(frame_idx, |x, y| {
+            x == 0 || y == 0 || x == width - 1 || y == height - 1
+        });
+    }
+
+    pub fn test_pattern_grid(&mut self, frame_idx: usize, step: usize) {
+        let step = step.max(1);
+        self.fill_pattern(frame_idx, |x, y| x % step == 0 || y % step == 0);
+    }
+
+    fn fill_pattern(&mut self, idx: usize, pattern: impl Fn(usize, usize) -> bool) {
+        let [width, _] = self.get_dimensions_pixels();
+        if let Some(layer) = self.get_active_layer_mut(idx) {
+            layer
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, pixel)| *pixel = pattern(i % width, i / width));
+        }
+    }
+
+    pub fn blit(
+        &mut self,
+        idx: usize,
+        pixels: &[bool],
+        pixels_width: usize,
+        x: usize,
+        y: usize,
+    ) -> Result<(), FrameError> {
+        if idx >= self.frames.len() {
+            return Err(FrameError::OutOfRange(idx));
+        }
+        if self.is_locked(idx) {
+            return Err(FrameError::Locked(idx));
+        }
+        let [width, height] = self.get_dimensions_pixels();
+        pixels
+            .chunks_exact(pixels_width)
+            .enumerate()
+            .flat_map(|(row, pixel_row)| {
+                pixel_row
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &pixel)| pixel)
+                    .map(move |(column, _)| [x + column, y + row])
+            })
+            .filter(|&[x, y]| x < width && y < height)
+            .try_for_each(|[x, y]| self.set(x, y, idx, true))
+    }
+
+    pub fn invert_frame(&mut self, idx: usize) {
+        if self.is_locked(idx) {
+            return;
+        }
+        match self.mode {
+            CanvasMode::Monochrome => self
+                .active_layer_mut(idx)
+                .iter_mut()
+                .for_each(|pixel| *pixel = !*pixel),
+            CanvasMode::Grayscale4Bit => {
+                let frame = &mut self.frames[idx];
+                frame.layers[self.active_layer]
+                    .iter_mut()
+                    .zip(&mut frame.layer_levels[self.active_layer])
+                    .for_each(|(pixel, level)| {
+                        *level = MAX_LEVEL - if *pixel { *level } else { 0 };
+                        *pixel = *level != 0;
+                    });
+            }
+        }
+    }
+
+    pub fn flip_horizontal(&mut self, idx: usize) {
+        if self.is_locked(idx) {
+            return;
+        }
+        let [width, _] = self.get_dimensions_pixels();
+        flip_horizontal_bitmap(self.active_layer_mut(idx), width);
+        flip_horizontal_bitmap(self.active_levels_mut(idx), width);
+    }
+
+    pub fn flip_vertical(&mut self, idx: usize) {
+        if self.is_locked(idx) {
+            return;
+        }
+        let [width, _] = self.get_dimensions_pixels();
+        flip_vertical_bitmap(self.active_layer_mut(idx), width);
+        flip_vertical_bitmap(self.active_levels_mut(idx), width);
+    }
+
+    pub fn rotate_canvas_cw(&mut self, allow_resize: bool) -> Result<(), DimensionError> {
+        if self.width != self.height && !allow_resize {
+            return Err(DimensionError::NotSquare {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        let [width, height] = self.get_dimensions_pixels();
+        self.frames.iter_mut().for_each(|frame| {
+            frame
+                .layers
+                .iter_mut()
+                .for_each(|layer| *layer = rotate_cw_bitmap(layer, width, height));
+            frame
+                .layer_levels
+                .iter_mut()
+                .for_each(|levels| *levels = rotate_cw_bitmap(levels, width, height));
+        });
+        (self.width, self.height) = (self.height, self.width);
+        Ok(())
+    }
+
+    pub fn get_frame_as_string(&self, idx: usize) -> String {
+        self.get_frame_as_string_formatted(idx, ByteFormat::Hex04)
+    }
+
+    pub fn get_frame_as_string_formatted(&self, idx: usize, format: ByteFormat) -> String {
+        format!(
+            "{{{}}}",
+            self.get_bytes(idx)
+                .map(|byte| format.format_byte(byte))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    pub fn get_frame_layer_as_string(
+        &self,
+        frame_idx: usize,
+        layer_idx: usize,
+        format: ByteFormat,
+    ) -> String {
+        let frame = &self.frames[frame_idx];
+        format!(
+            "{{{}}}",
+            match self.mode {
+                CanvasMode::Monochrome => pack_bits(&frame.layers[layer_idx]),
+                CanvasMode::Grayscale4Bit => pack_nibbles(
+                    &frame.layers[layer_idx]
+                        .iter()
+                        .zip(&frame.layer_levels[layer_idx])
+                        .map(|(&pixel, &level)| if pixel { level } else { 0 })
+                        .collect::<Vec<_>>()
+                ),
+            }
+            .into_iter()
+            .map(|byte| format.format_byte(byte))
+            .collect::<Vec<_>>()
+            .join(", ")
+        )
+    }
+
+    pub fn get_frame_as_c_header(&self, idx: usize, var_name: &str) -> String {
+        let [width, height] = self.get_dimensions_pixels();
+        let define_prefix = var_name.to_uppercase();
+        format!(
+            "#define {define_prefix}_WIDTH {width}\n\
+             #define {define_prefix}_HEIGHT {height}\n\
+             #define {define_prefix}_FRAMES {}\n\
+             \n\
+             {}const uint8_t {var_name}[({define_prefix}_WIDTH * {define_prefix}_HEIGHT + {}) / {}] = {};",
+            self.get_frame_count(),
+            self.packing_comment(),
+            self.mode.pixels_per_byte() - 1,
+            self.mode.pixels_per_byte(),
+            self.get_frame_as_string(idx)
+        )
+    }
+
+    fn packing_comment(&self) -> String {
+        let [width, _] = self.get_dimensions_pixels();
+        let mode_comment = match self.mode {
+            CanvasMode::Monochrome => "",
+            CanvasMode::Grayscale4Bit => {
+                "// 4-bit levels, two pixels per byte, high nibble first.\n"
+            }
+        };
+        let padding_comment = if width % self.mode.pixels_per_byte() == 0 {
+            ""
+        } else {
+            "// Rows are packed back to back, not padded to whole bytes; only the last byte is padded.\n"
+        };
+        format!("{mode_comment}{padding_comment}")
+    }
+
+    pub fn get_frame_as_bitset(&self, idx: usize, var_name: &str) -> String {
+        let bitmap = self.composite_frame(idx);
+        format!(
+            "constexpr std::bitset<{}> {var_name}(\"{}\");",
+            bitmap.len(),
+            bitmap
+                .iter()
+                .map(|&pixel| if pixel { '1' } else { '0' })
+                .collect::<String>()
+        )
+    }
+
+    pub fn get_frame_as_ht16k33(&self, idx: usize) -> [u8; 16] {
+        let [width, height] = self.get_dimensions_pixels();
+        let bitmap = self.composite_frame(idx);
+        let mut display_ram = [0; 16];
+        (0..height.min(8)).for_each(|y| {
+            (0..width.min(16)).for_each(|x| {
+                if bitmap[y * width + x] {
+                    display_ram[y * 2 + x / 8] |= 1 << (x % 8);
+                }
+            });
+        });
+        display_ram
+    }
+
+    pub fn get_frame_as_ht16k33_code(
+        &self,
+        idx: usize,
+        var_name: &str,
+        format: ByteFormat,
+    ) -> String {
+        format!(
+            "// HT16K33 display RAM, write starting at address 0x00.\n\
+             // Byte 2 * n holds ROW0-ROW7 of COMn, byte 2 * n + 1 holds ROW8-ROW15.\n\
+             // Bit k of each byte is the pixel in column k (+ 8 for odd bytes) of row n.\n\
+             const uint8_t {var_name}[16] = {{{}}};",
+            self.get_frame_as_ht16k33(idx)
+                .iter()
+                .map(|&byte| format.format_byte(byte))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    pub fn get_sequence_as_string_formatted(
+        &self,
+        format: ByteFormat,
+        include_notes: bool,
+    ) -> String {
+        if !include_notes {
+            return format!(
+                "{{{}}}",
+                (0..self.frames.len())
+                    .map(|i| self.get_frame_as_string_formatted(i, format))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        format!(
+            "{{\n{}\n}}",
+            self.frames
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| {
+                    let comments = frame
+                        .note
+                        .lines()
+                        .map(|line| format!("// {line}\n"))
+                        .collect::<String>();
+                    format!(
+                        "{comments}{}",
+                        self.get_frame_as_string_formatted(i, format)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",\n")
+        )
+    }
+
+    pub fn get_sequence_as_c_with_durations(
+        &self,
+        var_name: &str,
+        frame_rate: u16,
+        format: ByteFormat,
+        include_notes: bool,
+    ) -> String {
+        let [width, height] = self.get_dimensions_pixels();
+        format!(
+            "{}const uint8_t {var_name}[{}][{}] = {};\n\
+             const uint16_t {var_name}_delays[] = {{{}}};",
+            self.packing_comment(),
+            self.get_frame_count(),
+            (width * height).div_ceil(self.mode.pixels_per_byte()),
+            self.get_sequence_as_string_formatted(format, include_notes),
+            self.iter_frame_durations_ms(frame_rate)
+                .map(|duration| duration.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    pub fn slide(&mut self, idx: usize, direction: Direction, animation: SlideAnimation) {
+        let dimension = i32::from(match direction {
+            Direction::Top | Direction::Bottom => self.height,
+            Direction::Left | Direction::Right => self.width,
+            Direction::TopLeft
+            | Direction::TopRight
+            | Direction::BottomLeft
+            | Direction::BottomRight => self.width.max(self.height),
+        }) * i32::from(self.matrix_size);
+        if dimension == 0 {
+            return;
+        }
+
+        let vector = IVec::from_direction(direction);
+
+        (1..dimension).for_each(|_| self.duplicate_frame(idx));
+
+        let current_frame = self.frames[idx].layers[self.active_layer].clone();
+        let current_levels = self.frames[idx].layer_levels[self.active_layer].clone();
+        (0..dimension).rev().for_each(|i| {
+            let scaled_vector = vector
+                * match animation {
+                    SlideAnimation::SlideIn => dimension - i - 1,
+                    SlideAnimation::SlideOut => i,
+                };
+            self.shift_into(
+                idx + usize::try_from(i).unwrap(),
+                &current_frame,
+                &current_levels,
+                scaled_vector,
+            );
+        });
+    }
+
+    pub fn slide_smooth(
+        &mut self,
+        idx: usize,
+        direction: Direction,
+        pixels_total: usize,
+        steps: usize,
+    ) {
+        if steps == 0 {
+            return;
+        }
+
+        let vector = IVec::from_direction(direction);
+
+        (0..steps).for_each(|_| self.duplicate_frame(idx));
+
+        let current_frame = self.frames[idx].layers[self.active_layer].clone();
+        let current_levels = self.frames[idx].layer_levels[self.active_layer].clone();
+        let mut offset = 0;
+        let mut error = steps / 2;
+        (1..=steps).for_each(|step| {
+            offset += pixels_total / steps;
+            error += pixels_total % steps;
+            if error >= steps {
+                offset += 1;
+                error -= steps;
+            }
+            self.shift_into(
+                idx + step,
+                &current_frame,
+                &current_levels,
+                vector * i32::try_from(offset).unwrap_or(i32::MAX),
+            );
+        });
+    }
+
+    pub fn pixel_sort_animation(&mut self, base_idx: usize, axis: Axis, steps: usize) {
+        let [width, height] = self.get_dimensions_pixels();
+        let (line_count, line_length) = match axis {
+            Axis::Rows => (height, width),
+            Axis::Columns => (width, height),
+        };
+        let pixel_index = |line: usize, position: usize| match axis {
+            Axis::Rows => line * width + position,
+            Axis::Columns => position * width + line,
+        };
+
+        (0..steps).for_each(|_| self.duplicate_frame(base_idx));
+
+        let base = self.frames[base_idx].layers[self.active_layer].clone();
+        (1..=steps).for_each(|step| {
+            let sorted_lines = (line_count * step).div_ceil(steps);
+            let layer = self.active_layer_mut(base_idx + step);
+            (0..sorted_lines).for_each(|line| {
+                let lit = (0..line_length)
+                    .filter(|&position| base[pixel_index(line, position)])
+                    .count();
+                (0..line_length).for_each(|position| {
+                    layer[pixel_index(line, position)] = position >= line_length - lit;
+                });
+            });
+        });
+    }
+
+    pub fn shake_animation(&mut self, base_idx: usize, frames: usize, amplitude: u8) {
+        let mut rng = XorShift(SHAKE_SEED);
+
+        (0..frames).for_each(|_| self.duplicate_frame(base_idx));
+
+        let base = self.frames[base_idx].layers[self.active_layer].clone();
+        let base_levels = self.frames[base_idx].layer_levels[self.active_layer].clone();
+        (1..=frames).for_each(|step| {
+            let offset = IVec::new(rng.offset(amplitude), rng.offset(amplitude));
+            self.shift_into(base_idx + step, &base, &base_levels, offset);
+        });
+    }
+
+    fn shift_into(
+        &mut self,
+        frame_number: usize,
+        source: &[bool],
+        source_levels: &[u8],
+        offset: IVec,
+    ) {
+        if self.is_locked(frame_number) {
+            return;
+        }
+        let [width, height] = self
+            .get_dimensions_pixels()
+            .map(|dimension| i32::try_from(dimension).unwrap());
+        self.clear_frame(frame_number);
+        (0..width * height)
+            .map(|i| IVec::new(i % width, i / width))
+            .for_each(|current_pixel| {
+                let IVec { x: new_x, y: new_y } = current_pixel + offset;
+                if (0..width).contains(&new_x) && (0..height).contains(&new_y) {
+                    let source_idx =
+                        usize::try_from(current_pixel.y * width + current_pixel.x).unwrap();
+                    let new_idx = usize::try_from(new_y * width + new_x).unwrap();
+                    self.active_layer_mut(frame_number)[new_idx] = source[source_idx];
+                    self.active_levels_mut(frame_number)[new_idx] = source_levels[source_idx];
+                }
+            });
+    }
+}
+
+impl Index<[usize; 3]> for ImageSequence {
+    type Output = bool;
+
+    fn index(&self, index: [usize; 3]) -> &Self::Output {
+        self.get(index[0], index[1], index[2]).unwrap()
+    }
+}
+
+impl IndexMut<[usize; 3]> for ImageSequence {
+    fn index_mut(&mut self, [x, y, idx]: [usize; 3]) -> &mut Self::Output {
+        if self.is_locked(idx) {
+            self.discarded_pixel = self[[x, y, idx]];
+            return &mut self.discarded_pixel;
+        }
+        self.get_mut(x, y, idx).unwrap()
+    }
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    OutOfRange(usize),
+    LastFrame,
+    Locked(usize),
+    PixelOutOfRange(usize, usize),
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::OutOfRange(idx) => write!(f, "frame {} does not exist", idx + 1),
+            FrameError::LastFrame => write!(f, "cannot delete the only frame"),
+            FrameError::Locked(idx) => write!(f, "frame {} is locked", idx + 1),
+            FrameError::PixelOutOfRange(x, y) => {
+                write!(f, "pixel ({x}, {y}) is outside the canvas")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DimensionError {
+    NotSquare { width: u8, height: u8 },
+}
+
+impl Display for DimensionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DimensionError::NotSquare { width, height } => {
+                write!(f, "canvas is {width}×{height} matrices, not square")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct FrameStats {
+    pub lit: usize,
+    pub total: usize,
+}
+
+impl FrameStats {
+    pub fn fill_percentage(&self) -> f32 {
+        self.lit as f32 / self.total as f32 * 100.0
+    }
+}
+
+fn shift_bitmap<T: Copy + Default>(pixels: &[T], width: usize, dx: i8, dy: i8) -> Vec<T> {
+    let height = pixels.len() / width;
+    let mut shifted = vec![T::default(); pixels.len()];
+    pixels.iter().enumerate().for_each(|(i, &pixel)| {
+        let x = (i % width) as isize + isize::from(dx);
+        let y = (i / width) as isize + isize::from(dy);
+        if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+            if x < width && y < height {
+                shifted[y * width + x] = pixel;
+            }
+        }
+    });
+    shifted
+}
+
+pub fn dilate_bitmap(pixels: &[bool], width: usize) -> Vec<bool> {
+    apply_structuring_element(pixels, width, |neighbours| {
+        neighbours.iter().any(|&pixel| pixel)
+    })
+}
+
+pub fn erode_bitmap(pixels: &[bool], width: usize) -> Vec<bool> {
+    apply_structuring_element(pixels, width, |neighbours| {
+        neighbours.iter().all(|&pixel| pixel)
+    })
+}
+
+fn apply_structuring_element(
+    pixels: &[bool],
+    width: usize,
+    combine: impl Fn(&[bool]) -> bool,
+) -> Vec<bool> {
+    let height = pixels.len() / width;
+    (0..pixels.len())
+        .map(|i| {
+            let [x, y] = [i % width, i / width];
+            let neighbours = (y.saturating_sub(1)..=(y + 1).min(height - 1))
+                .flat_map(|y| {
+                    (x.saturating_sub(1)..=(x + 1).min(width - 1))
+                        .map(move |x| pixels[y * width + x])
+                })
+                .collect::<Vec<_>>();
+            combine(&neighbours)
+        })
+        .collect()
+}
+
+pub fn flip_horizontal_bitmap<T>(pixels: &mut [T], width: usize) {
+    pixels.chunks_exact_mut(width).for_each(<[T]>::reverse);
+}
+
+pub fn flip_vertical_bitmap<T>(pixels: &mut [T], width: usize) {
+    let height = pixels.len() / width;
+    (0..height / 2).for_each(|row| {
+        let (top, bottom) = pixels.split_at_mut((height - 1 - row) * width);
+        top[row * width..(row + 1) * width].swap_with_slice(&mut bottom[..width]);
+    });
+}
+
+fn rotate_cw_bitmap<T: Copy>(pixels: &[T], width: usize, height: usize) -> Vec<T> {
+    (0..width * height)
+        .map(|i| {
+            let (new_x, new_y) = (i % height, i / height);
+            pixels[(height - 1 - new_x) * width + new_y]
+        })
+        .collect()
+}
+
+fn bits_to_byte(bits: &[bool]) -> u8 {
+    bits.iter().fold(0, |byte, &bit| byte << 1 | bit as u8)
+}
+
+fn pack_nibbles(levels: &[u8]) -> Vec<u8> {
+    levels
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| bits_to_byte(chunk) << (8 - chunk.len()))
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+struct PackedBitmap(Vec<u8>);
+
+#[cfg(feature = "serde")]
+impl PackedBitmap {
+    fn pack(bits: &[bool]) -> Self {
+        Self(pack_bits(bits))
+    }
+
+    fn unpack(&self) -> Vec<bool> {
+        self.0
+            .iter()
+            .flat_map(|&byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1))
+            .collect()
+    }
+
+    fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{byte:02X}")).collect()
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() % 2 != 0 || !hex.is_ascii() {
+            return None;
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()
+            .map(Self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for PackedBitmap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PackedBitmap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PackedBitmapVisitor;
+
+        impl<'de> Visitor<'de> for PackedBitmapVisitor {
+            type Value = PackedBitmap;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "packed bitmap bytes, a hex string or an array of booleans"
+                )
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                PackedBitmap::from_hex(v).ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(PackedBitmap(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(PackedBitmap(v))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bits = Vec::new();
+                while let Some(bit) = seq.next_element()? {
+                    bits.push(bit);
+                }
+                Ok(PackedBitmap::pack(&bits))
+            }
+        }
+
+        deserializer.deserialize_any(PackedBitmapVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod packed_layers {
+    use super::PackedBitmap;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        layers: &[Vec<bool>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(layers.iter().map(|layer| PackedBitmap::pack(layer)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<bool>>, D::Error> {
+        Ok(Vec::<PackedBitmap>::deserialize(deserializer)?
+            .iter()
+            .map(PackedBitmap::unpack)
+            .collect())
+    }
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn offset(&mut self, amplitude: u8) -> i32 {
+        let span = u64::from(amplitude) * 2 + 1;
+        i32::try_from(self.next() % span).unwrap() - i32::from(amplitude)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct IVec {
+    x: i32,
+    y: i32,
+}
+
+impl IVec {
+    fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    fn from_direction(direction: Direction) -> Self {
+        match direction {
+            Direction::Top => Self::new(0, -1),
+            Direction::Left => Self::new(-1, 0),
+            Direction::Bottom => Self::new(0, 1),
+            Direction::Right => Self::new(1, 0),
+            Direction::TopLeft => Self::new(-1, -1),
+            Direction::TopRight => Self::new(1, -1),
+            Direction::BottomLeft => Self::new(-1, 1),
+            Direction::BottomRight => Self::new(1, 1),
+        }
+    }
+}
+
+impl Mul<i32> for IVec {
+    type Output = IVec;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self {
+            x: self.x.saturating_mul(rhs),
+            y: self.y.saturating_mul(rhs),
+        }
+    }
+}
+
+impl Add<IVec> for IVec {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x.saturating_add(rhs.x),
+            y: self.y.saturating_add(rhs.y),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Top,
+    Left,
+    Bottom,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Direction::Top => "Top",
+                Direction::Left => "Left",
+                Direction::Bottom => "Bottom",
+                Direction::Right => "Right",
+                Direction::TopLeft => "Top left",
+                Direction::TopRight => "Top right",
+                Direction::BottomLeft => "Bottom left",
+                Direction::BottomRight => "Bottom right",
+            }
+        )
+    }
+}
+
+impl Direction {
+    pub fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [
+            Self::Top,
+            Self::Left,
+            Self::Bottom,
+            Self::Right,
+            Self::TopLeft,
+            Self::TopRight,
+            Self::BottomLeft,
+            Self::BottomRight,
+        ]
+        .into_iter()
+    }
+}
 
 #[derive(Clone, Copy)]
 pub enum SlideAnimation {
@@ -288,3 +1814,707 @@ impl SlideAnimation {
         [Self::SlideIn, Self::SlideOut].into_iter()
     }
 }
+
+#[derive(Clone, Copy)]
+pub enum Axis {
+    Rows,
+    Columns,
+}
+
+impl Display for Axis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Axis::Rows => "Rows",
+                Axis::Columns => "Columns",
+            }
+        )
+    }
+}
+
+impl Axis {
+    pub fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Rows, Self::Columns].into_iter()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TweenMethod {
+    pub steps: usize,
+    pub dither: DitherMethod,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DitherMethod {
+    Threshold,
+    Ordered,
+}
+
+impl Display for DitherMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DitherMethod::Threshold => "Threshold",
+                DitherMethod::Ordered => "Ordered",
+            }
+        )
+    }
+}
+
+impl DitherMethod {
+    const BAYER_MATRIX: [[u8; 4]; 4] =
+        [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+    pub fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Threshold, Self::Ordered].into_iter()
+    }
+
+    fn apply(self, value: f32, x: usize, y: usize) -> bool {
+        match self {
+            DitherMethod::Threshold => value >= 0.5,
+            DitherMethod::Ordered => {
+                value > (f32::from(Self::BAYER_MATRIX[y % 4][x % 4]) + 0.5) / 16.0
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CanvasMode {
+    #[default]
+    Monochrome,
+    Grayscale4Bit,
+}
+
+impl Display for CanvasMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CanvasMode::Monochrome => "Monochrome",
+                CanvasMode::Grayscale4Bit => "Grayscale (4-bit)",
+            }
+        )
+    }
+}
+
+impl CanvasMode {
+    pub fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Monochrome, Self::Grayscale4Bit].into_iter()
+    }
+
+    pub fn pixels_per_byte(self) -> usize {
+        match self {
+            CanvasMode::Monochrome => 8,
+            CanvasMode::Grayscale4Bit => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ByteFormat {
+    Hex04,
+    Hex02,
+    Decimal,
+    Binary8,
+}
+
+impl Display for ByteFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ByteFormat::Hex04 => "0x0F",
+                ByteFormat::Hex02 => "0F",
+                ByteFormat::Decimal => "15",
+                ByteFormat::Binary8 => "0b00001111",
+            }
+        )
+    }
+}
+
+impl ByteFormat {
+    pub fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Hex04, Self::Hex02, Self::Decimal, Self::Binary8].into_iter()
+    }
+
+    pub fn format_byte(self, byte: u8) -> String {
+        match self {
+            ByteFormat::Hex04 => format!("{byte:#04X}"),
+            ByteFormat::Hex02 => format!("{byte:02X}"),
+            ByteFormat::Decimal => format!("{byte}"),
+            ByteFormat::Binary8 => format!("{byte:#010b}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn new_creates_single_blank_frame() {
+        let image_sequence = ImageSequence::new(2, 1);
+        assert_eq!(image_sequence.get_frame_count(), 1);
+        assert_eq!(image_sequence.get_dimensions_pixels(), [16, 8]);
+        assert!(image_sequence
+            .composite_frame(0)
+            .iter()
+            .all(|&pixel| !pixel));
+    }
+
+    #[test]
+    fn add_and_delete_frame_adjust_frame_count() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        image_sequence.add_frame();
+        assert_eq!(image_sequence.get_frame_count(), 3);
+        assert!(image_sequence.delete_frame(1).is_ok());
+        assert_eq!(image_sequence.get_frame_count(), 2);
+        assert!(matches!(
+            image_sequence.delete_frame(2),
+            Err(FrameError::OutOfRange(2))
+        ));
+        assert!(image_sequence.delete_frame(0).is_ok());
+        assert!(matches!(
+            image_sequence.delete_frame(0),
+            Err(FrameError::LastFrame)
+        ));
+        assert_eq!(image_sequence.get_frame_count(), 1);
+    }
+
+    #[test]
+    fn frame_notes_follow_their_frames() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        *image_sequence.get_frame_note_mut(1).unwrap() = String::from("second");
+        image_sequence.insert_frame(0);
+        assert_eq!(image_sequence.get_frame_note_mut(2).unwrap(), "second");
+        assert!(image_sequence.delete_frame(0).is_ok());
+        assert_eq!(image_sequence.get_frame_note_mut(1).unwrap(), "second");
+        assert!(image_sequence
+            .get_sequence_as_string_formatted(ByteFormat::Hex04, true)
+            .contains("// second\n{"));
+    }
+
+    #[test]
+    fn total_duration_uses_frame_rate_for_default_durations() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        image_sequence.add_frame();
+        assert_eq!(image_sequence.total_duration_ms(10), 300);
+        *image_sequence.get_frame_duration_mut(1).unwrap() = 500;
+        assert_eq!(image_sequence.total_duration_ms(10), 700);
+    }
+
+    #[test]
+    fn shake_animation_is_reproducible() {
+        let shake = || {
+            let mut image_sequence = ImageSequence::new(1, 1);
+            *image_sequence.get_mut(3, 3, 0).unwrap() = true;
+            image_sequence.shake_animation(0, 6, 2);
+            image_sequence.iter_frames().collect::<Vec<_>>()
+        };
+        let frames = shake();
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames, shake());
+        assert!(frames[1..]
+            .iter()
+            .all(|bitmap| { bitmap.iter().filter(|&&pixel| pixel).count() == 1 }));
+    }
+
+    #[test]
+    fn bitset_lists_pixels_in_row_major_order() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        *image_sequence.get_mut(0, 0, 0).unwrap() = true;
+        *image_sequence.get_mut(7, 7, 0).unwrap() = true;
+        let bitset = image_sequence.get_frame_as_bitset(0, "frame");
+        assert_eq!(
+            bitset,
+            format!("constexpr std::bitset<64> frame(\"1{}1\");", "0".repeat(62))
+        );
+    }
+
+    #[test]
+    fn background_colors_follow_frames_and_export_as_c() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        assert_eq!(
+            image_sequence.get_background_colors_as_c("image", [0, 0, 0]),
+            None
+        );
+
+        *image_sequence.get_background_color_mut(0).unwrap() = Some([0xFF, 0x00, 0x10]);
+        image_sequence.duplicate_frame(0);
+        assert!(image_sequence.move_down(1));
+        assert_eq!(
+            image_sequence.get_background_color(0),
+            Some([0xFF, 0x00, 0x10])
+        );
+        assert_eq!(image_sequence.get_background_color(1), None);
+        assert_eq!(
+            image_sequence.get_background_color(2),
+            Some([0xFF, 0x00, 0x10])
+        );
+
+        image_sequence.delete_frame(0).unwrap();
+        assert_eq!(
+            image_sequence.get_background_colors_as_c("image", [0x00, 0x00, 0x00]),
+            Some(String::from(
+                "const uint8_t image_backgrounds[2][3] = {{0x00, 0x00, 0x00}, {0xFF, 0x00, 0x10}};"
+            ))
+        );
+    }
+
+    #[test]
+    fn locked_frames_reject_edits_and_follow_reordering() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        image_sequence.toggle_lock(1);
+        assert!(image_sequence.get_mut(0, 0, 1).is_none());
+        assert!(matches!(
+            image_sequence.delete_frame(1),
+            Err(FrameError::Locked(1))
+        ));
+        image_sequence.insert_frame(0);
+        assert!(image_sequence.is_locked(2));
+        assert!(image_sequence.move_up(2));
+        assert!(image_sequence.is_locked(1));
+        assert!(image_sequence.delete_frame(0).is_ok());
+        assert!(image_sequence.is_locked(0));
+        assert!(matches!(
+            image_sequence.set(0, 0, 0, true),
+            Err(FrameError::Locked(0))
+        ));
+        assert!(matches!(
+            image_sequence.blit(0, &[true], 1, 0, 0),
+            Err(FrameError::Locked(0))
+        ));
+        image_sequence[[1, 1, 0]] = true;
+        assert!(!image_sequence[[1, 1, 0]]);
+        image_sequence.toggle_lock(0);
+        assert!(image_sequence.get_mut(0, 0, 0).is_some());
+        assert!(image_sequence.set(0, 0, 0, true).is_ok());
+        assert!(matches!(
+            image_sequence.set(8, 0, 0, true),
+            Err(FrameError::PixelOutOfRange(8, 0))
+        ));
+        assert!(image_sequence.blit(0, &[true; 4], 2, 7, 7).is_ok());
+        assert_eq!(image_sequence.get(7, 7, 0), Some(&true));
+    }
+
+    #[test]
+    fn tweens_skip_keyframe_pairs_spanning_locked_frames() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        (0..4).for_each(|_| image_sequence.add_frame());
+        [0, 2, 4]
+            .into_iter()
+            .for_each(|idx| image_sequence.toggle_keyframe(idx));
+        image_sequence.toggle_lock(3);
+        image_sequence.generate_tweens(TweenMethod {
+            steps: 2,
+            dither: DitherMethod::Threshold,
+        });
+        assert_eq!(image_sequence.get_frame_count(), 6);
+        assert!(image_sequence.is_locked(4));
+        assert!(image_sequence.is_keyframe(3));
+        assert!(image_sequence.is_keyframe(5));
+    }
+
+    #[test]
+    fn test_patterns_light_expected_pixels() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.test_pattern_all_on(0);
+        assert_eq!(image_sequence.frame_stats(0).lit, 64);
+        image_sequence.test_pattern_border(0);
+        assert_eq!(image_sequence.frame_stats(0).lit, 28);
+        assert_eq!(image_sequence.get(1, 1, 0), Some(&false));
+        image_sequence.test_pattern_grid(0, 4);
+        assert_eq!(image_sequence.frame_stats(0).lit, 28);
+        assert_eq!(image_sequence.get(4, 5, 0), Some(&true));
+        assert_eq!(image_sequence.get(5, 5, 0), Some(&false));
+    }
+
+    #[test]
+    fn trim_blank_frames_keeps_inner_and_last_frames() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        (0..4).for_each(|_| image_sequence.add_frame());
+        *image_sequence.get_mut(0, 0, 1).unwrap() = true;
+        *image_sequence.get_mut(0, 0, 3).unwrap() = true;
+        assert_eq!(image_sequence.count_leading_blank(), 1);
+        assert_eq!(image_sequence.count_trailing_blank(), 1);
+        assert_eq!(image_sequence.trim_blank_frames(), 2);
+        assert_eq!(image_sequence.get_frame_count(), 3);
+        assert_eq!(image_sequence.get(0, 0, 0), Some(&true));
+
+        let mut blank_sequence = ImageSequence::new(1, 1);
+        blank_sequence.add_frame();
+        assert_eq!(blank_sequence.trim_blank_frames(), 1);
+        assert_eq!(blank_sequence.get_frame_count(), 1);
+    }
+
+    #[test]
+    fn hamming_distance_counts_differing_pixels() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        assert_eq!(image_sequence.hamming_distance(0, 1), 0);
+        *image_sequence.get_mut(0, 0, 0).unwrap() = true;
+        *image_sequence.get_mut(1, 0, 1).unwrap() = true;
+        assert_eq!(image_sequence.hamming_distance(0, 1), 2);
+        assert_eq!(image_sequence.hamming_distance(1, 0), 2);
+    }
+
+    #[test]
+    fn bytes_with_offset_shift_output_only() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        *image_sequence.get_mut(0, 0, 0).unwrap() = true;
+        let bytes = image_sequence
+            .get_bytes_with_offset(0, 2, 1)
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, [0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert!(image_sequence
+            .get_bytes_with_offset(0, -1, 0)
+            .all(|byte| byte == 0));
+        assert_eq!(image_sequence.get_bytes(0).next(), Some(0x80));
+    }
+
+    #[test]
+    fn matrix_size_sets_pixel_dimensions_and_pads_bytes() {
+        let mut image_sequence = ImageSequence::with_matrix_size(1, 1, 5);
+        assert_eq!(image_sequence.get_dimensions_pixels(), [5, 5]);
+        *image_sequence.get_mut(4, 4, 0).unwrap() = true;
+        let bytes = image_sequence.get_bytes(0).collect::<Vec<_>>();
+        assert_eq!(bytes, [0x00, 0x00, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn c_with_durations_uses_byte_format() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        *image_sequence.get_mut(0, 0, 1).unwrap() = true;
+        *image_sequence.get_frame_duration_mut(1).unwrap() = 250;
+        assert_eq!(
+            image_sequence.get_sequence_as_c_with_durations(
+                "image",
+                10,
+                ByteFormat::Decimal,
+                false
+            ),
+            "const uint8_t image[2][8] = {{0, 0, 0, 0, 0, 0, 0, 0}, {128, 0, 0, 0, 0, 0, 0, 0}};\n\
+             const uint16_t image_delays[] = {100, 250};"
+        );
+    }
+
+    #[test]
+    fn xbm_rows_are_padded_to_whole_bytes() {
+        let mut image_sequence = ImageSequence::with_matrix_size(1, 1, 5);
+        *image_sequence.get_mut(0, 0, 0).unwrap() = true;
+        *image_sequence.get_mut(4, 1, 0).unwrap() = true;
+        *image_sequence.get_mut(2, 4, 0).unwrap() = true;
+        let bytes = image_sequence.get_frame_as_xbm_bytes(0);
+        assert_eq!(bytes, [0x01, 0x10, 0x00, 0x00, 0x04]);
+
+        let loaded = ImageSequence::from_xbm_bytes(&bytes, 5, 5).unwrap();
+        assert!(ImageSequence::from_xbm_bytes(&bytes[..4], 5, 5).is_none());
+        assert!(ImageSequence::from_xbm_bytes(&bytes, 0, 5).is_none());
+        assert_eq!(loaded.get(0, 0, 0), Some(&true));
+        assert_eq!(loaded.get(4, 1, 0), Some(&true));
+        assert_eq!(loaded.get(2, 4, 0), Some(&true));
+        assert_eq!(loaded.get(1, 0, 0), Some(&false));
+
+        assert!(image_sequence
+            .get_frame_as_c_header(0, "image")
+            .contains("// Rows are packed back to back"));
+        assert!(!ImageSequence::new(1, 1)
+            .get_frame_as_c_header(0, "image")
+            .contains("//"));
+    }
+
+    #[test]
+    fn c_header_defines_dimensions_and_frame_count() {
+        let mut image_sequence = ImageSequence::new(2, 1);
+        image_sequence.add_frame();
+        let header = image_sequence.get_frame_as_c_header(0, "logo");
+        assert!(header
+            .starts_with("#define LOGO_WIDTH 16\n#define LOGO_HEIGHT 8\n#define LOGO_FRAMES 2\n"));
+        assert!(header.contains("const uint8_t logo[(LOGO_WIDTH * LOGO_HEIGHT + 7) / 8] = {"));
+    }
+
+    #[test]
+    fn bmp_rows_are_stored_bottom_up_and_padded() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        *image_sequence.get_mut(0, 0, 0).unwrap() = true;
+        let bmp = image_sequence.get_frame_as_bmp(0);
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(bmp.len(), 62 + 8 * 4);
+        assert_eq!(u32::from_le_bytes(bmp[2..6].try_into().unwrap()), 94);
+        assert_eq!(&bmp[62..66], [0x00; 4]);
+        assert_eq!(&bmp[90..94], [0x80, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn move_up_and_down_fail_at_boundaries() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        assert!(!image_sequence.move_up(0));
+        assert!(!image_sequence.move_down(1));
+        assert!(image_sequence.move_down(0));
+        assert!(image_sequence.move_up(1));
+    }
+
+    #[test]
+    fn get_returns_none_out_of_bounds() {
+        let image_sequence = ImageSequence::new(1, 1);
+        assert_eq!(image_sequence.get(7, 7, 0), Some(&false));
+        assert_eq!(image_sequence.get(8, 0, 0), None);
+        assert_eq!(image_sequence.get(0, 8, 0), None);
+        assert_eq!(image_sequence.get(0, 0, 1), None);
+    }
+
+    #[test]
+    fn bits_to_byte_packs_most_significant_bit_first() {
+        assert_eq!(bits_to_byte(&[false; 8]), 0x00);
+        assert_eq!(bits_to_byte(&[true; 8]), 0xFF);
+        assert_eq!(
+            bits_to_byte(&[true, false, false, false, false, false, false, false]),
+            0x80
+        );
+        assert_eq!(
+            bits_to_byte(&[false, true, false, true, false, false, true, true]),
+            0x53
+        );
+    }
+
+    #[test]
+    fn get_frame_as_string_formats_known_bitmap() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence[[0, 0, 0]] = true;
+        image_sequence[[7, 1, 0]] = true;
+        assert_eq!(
+            image_sequence.get_frame_as_string(0),
+            "{0x80, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00}"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn packed_bitmap_round_trip() {
+        let bits = (0..64).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let packed = PackedBitmap::pack(&bits);
+        assert_eq!(packed.0.len(), 8);
+        assert_eq!(packed.unpack(), bits);
+    }
+
+    #[test]
+    fn grayscale_bytes_are_nibble_packed() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.set_mode(CanvasMode::Grayscale4Bit);
+        image_sequence.set_level(0, 0, 0, 3);
+        image_sequence.set_level(1, 0, 0, MAX_LEVEL);
+        let bytes = image_sequence.get_bytes(0).collect::<Vec<_>>();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(bytes[0], 0x3F);
+        assert!(bytes[1..].iter().all(|&byte| byte == 0));
+
+        image_sequence.invert_frame(0);
+        assert_eq!(image_sequence.get_level(0, 0, 0), Some(MAX_LEVEL - 3));
+        assert_eq!(image_sequence.get_level(1, 0, 0), Some(0));
+        assert_eq!(image_sequence.get_level(2, 0, 0), Some(MAX_LEVEL));
+    }
+
+    #[test]
+    fn grayscale_c_output_is_sized_for_nibbles() {
+        let mut image_sequence = ImageSequence::with_matrix_size(1, 1, 3);
+        image_sequence.set_mode(CanvasMode::Grayscale4Bit);
+        image_sequence.set_level(0, 0, 0, 7);
+        let header = image_sequence.get_frame_as_c_header(0, "logo");
+        assert!(header.contains("const uint8_t logo[(LOGO_WIDTH * LOGO_HEIGHT + 1) / 2] = {"));
+        assert!(header.contains("// 4-bit levels"));
+        assert!(header.ends_with("{0x70, 0x00, 0x00, 0x00, 0x00};"));
+
+        let sequence =
+            image_sequence.get_sequence_as_c_with_durations("logo", 10, ByteFormat::Hex04, false);
+        assert!(sequence.contains("const uint8_t logo[1][5] = "));
+        assert_eq!(
+            image_sequence.get_frame_layer_as_string(0, 0, ByteFormat::Hex04),
+            "{0x70, 0x00, 0x00, 0x00, 0x00}"
+        );
+    }
+
+    #[test]
+    fn grayscale_levels_are_stored_per_layer() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.set_mode(CanvasMode::Grayscale4Bit);
+        image_sequence.set_level(0, 0, 0, 3);
+        image_sequence.add_layer();
+        image_sequence.set_level(0, 0, 0, 9);
+        image_sequence.set_level(1, 0, 0, 5);
+        assert_eq!(image_sequence.get_level(0, 0, 0), Some(9));
+        assert_eq!(image_sequence.composite_frame_levels(0)[1], 5);
+
+        image_sequence.toggle_layer(0, 1);
+        assert_eq!(image_sequence.get_level(0, 0, 0), Some(3));
+        assert_eq!(image_sequence.get_level(1, 0, 0), Some(0));
+
+        image_sequence.toggle_layer(0, 1);
+        image_sequence.delete_layer(0);
+        assert_eq!(image_sequence.get_level(0, 0, 0), Some(9));
+        image_sequence.set_mode(CanvasMode::Monochrome);
+        assert_eq!(image_sequence.composite_frame_levels(0)[0], MAX_LEVEL);
+    }
+
+    #[test]
+    fn flip_bitmaps_mirror_rows_and_columns() {
+        let mut pixels = [1, 2, 3, 4, 5, 6];
+        flip_horizontal_bitmap(&mut pixels, 3);
+        assert_eq!(pixels, [3, 2, 1, 6, 5, 4]);
+        flip_vertical_bitmap(&mut pixels, 2);
+        assert_eq!(pixels, [5, 4, 1, 6, 3, 2]);
+    }
+
+    #[test]
+    fn dilate_and_erode_use_a_3x3_structuring_element() {
+        let mut pixels = vec![false; 25];
+        pixels[12] = true;
+        let dilated = dilate_bitmap(&pixels, 5);
+        assert_eq!(dilated.iter().filter(|&&pixel| pixel).count(), 9);
+        assert!(dilated[6] && dilated[18] && !dilated[0]);
+        assert_eq!(erode_bitmap(&dilated, 5), pixels);
+        assert!(erode_bitmap(&[true; 4], 2).iter().all(|&pixel| pixel));
+    }
+
+    #[test]
+    fn rotate_canvas_cw_moves_pixels_and_swaps_dimensions() {
+        let mut square = ImageSequence::new(1, 1);
+        square[[1, 0, 0]] = true;
+        assert!(square.rotate_canvas_cw(false).is_ok());
+        assert!(square[[7, 1, 0]]);
+
+        let mut wide = ImageSequence::new(2, 1);
+        wide[[15, 0, 0]] = true;
+        assert!(matches!(
+            wide.rotate_canvas_cw(false),
+            Err(DimensionError::NotSquare {
+                width: 2,
+                height: 1
+            })
+        ));
+        assert!(wide.rotate_canvas_cw(true).is_ok());
+        assert_eq!(wide.get_dimensions_pixels(), [8, 16]);
+        assert!(wide[[7, 15, 0]]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn packed_bitmap_hex_round_trip() {
+        let packed = PackedBitmap(vec![0x00, 0x0F, 0xA5, 0xFF]);
+        assert_eq!(packed.to_hex(), "000FA5FF");
+        assert_eq!(PackedBitmap::from_hex("000FA5FF").unwrap().0, packed.0);
+        assert!(PackedBitmap::from_hex("0F0").is_none());
+        assert!(PackedBitmap::from_hex("ZZ").is_none());
+    }
+
+    #[test]
+    fn xbm_round_trip() {
+        let bytes = (0..32).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let image_sequence = ImageSequence::from_xbm_bytes(&bytes, 16, 16).unwrap();
+        assert_eq!(image_sequence.get_dimensions_pixels(), [16, 16]);
+        assert_eq!(image_sequence[[0, 0, 0]], bytes[0] & 1 == 1);
+        assert_eq!(image_sequence.get_frame_as_xbm_bytes(0), bytes);
+    }
+
+    #[test]
+    fn slide_produces_one_frame_per_pixel_step() {
+        let mut image_sequence = ImageSequence::new(2, 1);
+        image_sequence.slide(0, Direction::Left, SlideAnimation::SlideOut);
+        assert_eq!(image_sequence.get_frame_count(), 16);
+
+        let mut image_sequence = ImageSequence::new(2, 1);
+        image_sequence.slide(0, Direction::Top, SlideAnimation::SlideIn);
+        assert_eq!(image_sequence.get_frame_count(), 8);
+    }
+
+    fn image_sequence_strategy() -> impl Strategy<Value = ImageSequence> {
+        (1..=8u8, 1..=8u8, 1..=4usize, 1..=3usize, any::<bool>()).prop_map(
+            |(width, height, frame_count, layer_count, lit)| {
+                let mut image_sequence = ImageSequence::new(width, height);
+                (1..frame_count).for_each(|_| image_sequence.add_frame());
+                (1..layer_count).for_each(|_| {
+                    image_sequence.add_layer();
+                    (0..image_sequence.get_frame_count()).for_each(|idx| {
+                        image_sequence.get_active_layer_mut(idx).unwrap().fill(lit);
+                    });
+                });
+                image_sequence.set_active_layer(0);
+                image_sequence
+            },
+        )
+    }
+
+    fn image_sequence_with_pixel_strategy(
+    ) -> impl Strategy<Value = (ImageSequence, usize, usize, usize)> {
+        image_sequence_strategy().prop_flat_map(|image_sequence| {
+            let [width, height] = image_sequence.get_dimensions_pixels();
+            let frame_count = image_sequence.get_frame_count();
+            (Just(image_sequence), 0..width, 0..height, 0..frame_count)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn get_never_panics(
+            image_sequence in image_sequence_strategy(),
+            x in 0..100usize,
+            y in 0..100usize,
+            idx in 0..8usize,
+        ) {
+            let [width, height] = image_sequence.get_dimensions_pixels();
+            let in_bounds = x < width && y < height && idx < image_sequence.get_frame_count();
+            prop_assert_eq!(image_sequence.get(x, y, idx).is_some(), in_bounds);
+        }
+
+        #[test]
+        fn index_matches_get(
+            (image_sequence, x, y, idx) in image_sequence_with_pixel_strategy(),
+        ) {
+            prop_assert_eq!(Some(&image_sequence[[x, y, idx]]), image_sequence.get(x, y, idx));
+        }
+
+        #[test]
+        fn get_mut_write_is_visible_in_get(
+            (mut image_sequence, x, y, idx) in image_sequence_with_pixel_strategy(),
+            value: bool,
+        ) {
+            *image_sequence.get_mut(x, y, idx).unwrap() = value;
+            prop_assert_eq!(image_sequence.get(x, y, idx), Some(&value));
+            image_sequence[[x, y, idx]] = value;
+            prop_assert_eq!(image_sequence[[x, y, idx]], value);
+        }
+
+        #[test]
+        fn composite_combines_enabled_layers(
+            (image_sequence, x, y, idx) in image_sequence_with_pixel_strategy(),
+        ) {
+            let expected = (0..image_sequence.get_layer_count())
+                .filter(|&layer| image_sequence.is_layer_enabled(idx, layer))
+                .any(|layer| image_sequence.frames[idx].layers[layer][y * image_sequence.get_dimensions_pixels()[0] + x]);
+            prop_assert_eq!(image_sequence.get_composite(x, y, idx), Some(expected));
+        }
+
+        #[test]
+        fn iter_pixels_yields_every_pixel(image_sequence in image_sequence_strategy()) {
+            let [width, height] = image_sequence.get_dimensions_pixels();
+            (0..image_sequence.get_frame_count()).for_each(|idx| {
+                assert_eq!(image_sequence.iter_pixels(idx).unwrap().count(), width * height);
+            });
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod image_matrix;
+pub mod pixel_font;
+
+pub use image_matrix::{Direction, ImageSequence, SlideAnimation};
+pub use pixel_font::PixelFont;
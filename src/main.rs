@@ -1,53 +1,63 @@
-use crate::image_matrix::{ImageSequence, SlideAnimation};
+use arboard::{Clipboard, ImageData};
+use base64::engine::general_purpose;
+use base64::Engine;
 use eframe::egui::{
-    menu, Button, CentralPanel, Color32, Context, DragValue, Key, KeyboardShortcut, Modifiers,
-    Painter, PointerButton, Pos2, Rect, Rounding, ScrollArea, Sense, Stroke, TextEdit,
-    TopBottomPanel, Ui, Vec2, Window,
+    menu, Align2, Area, Button, CentralPanel, Checkbox, Color32, ColorImage, ComboBox, Context,
+    CursorIcon, DragValue, FontId, Frame, Grid, ImageButton, Key, KeyboardShortcut, Modifiers,
+    Painter, PointerButton, Pos2, ProgressBar, Rect, RichText, Rounding, ScrollArea, Sense, Shape,
+    Stroke, TextEdit, TextureHandle, TextureOptions, TopBottomPanel, Ui, Vec2, Window,
 };
-use eframe::{App, NativeOptions};
+use eframe::{App, NativeOptions, Storage};
+use fontdue::{Font, FontSettings};
 use image::codecs::gif::{GifEncoder, Repeat};
 use image::imageops::{BiLevel, FilterType};
 use image::io::Reader;
-use image::{imageops, Delay, Rgba, RgbaImage};
-use rfd::{FileDialog, MessageDialog};
+use image::{
+    imageops, Delay, DynamicImage, GrayImage, ImageFormat, ImageOutputFormat, Rgba, RgbaImage,
+};
+use maturski::image_matrix::{
+    dilate_bitmap, erode_bitmap, flip_horizontal_bitmap, flip_vertical_bitmap, Axis, ByteFormat,
+    CanvasMode, DimensionError, Direction, DitherMethod, FrameError, ImageSequence, SlideAnimation,
+    TweenMethod, DEFAULT_MATRIX_SIZE, MAX_LEVEL,
+};
+use maturski::pixel_font::PixelFont;
+use prost::Message;
+use rfd::{FileDialog, MessageButtons, MessageDialog};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::fmt::{Display, Formatter};
 use std::fs;
 use std::fs::File;
+use std::io::Cursor;
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
 
-mod image_matrix;
-
 fn main() {
     eframe::run_native(
-        "",
+        "maturski",
         NativeOptions::default(),
-        Box::new(|_cc| {
-            Box::new(MainWindow {
-                project: Project {
-                    image_sequence: ImageSequence::new(4, 4),
-                    frame_rate: 10,
-                },
-                current_file: None,
-                scale: 1,
-                current_frame: 1,
-                show_grid: false,
-                stoke_thickness: 1.0,
-                onion_skin: false,
-                onion_opacity: 0.05,
-                display_color: [0xFF, 0x00, 0x00],
-                new_file_dialog: NewFileDialog {
-                    show: false,
-                    width: 4,
-                    height: 4,
-                    frame_rate: 10,
-                },
-                code_display: CodeDisplay::SingleFrame,
-                play: false,
-                last_frame_delta: Instant::now(),
-            })
+        Box::new(|cc| {
+            let default_project_settings: DefaultProjectSettings = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, DefaultProjectSettings::STORAGE_KEY))
+                .unwrap_or_default();
+            let recent_files = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, MainWindow::RECENT_FILES_KEY))
+                .unwrap_or_default();
+            let saved_templates = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, MainWindow::SAVED_TEMPLATES_KEY))
+                .unwrap_or_default();
+            Box::new(MainWindow::new(
+                default_project_settings,
+                recent_files,
+                saved_templates,
+            ))
         }),
     )
     .unwrap();
@@ -55,42 +65,501 @@ fn main() {
 
 #[derive(Serialize, Deserialize)]
 struct Project {
+    #[serde(default)]
+    version: u32,
     image_sequence: ImageSequence,
     frame_rate: u16,
+    #[serde(default)]
+    thumbnail: Option<Vec<u8>>,
+    #[serde(default)]
+    snapshots: Vec<(String, ImageSequence)>,
+    #[serde(default)]
+    output_offset: (i8, i8),
+    #[serde(default)]
+    hardware_fps_limit: Option<u16>,
+}
+
+impl Project {
+    const VERSION: u32 = 3;
+
+    fn new(image_sequence: ImageSequence, frame_rate: u16) -> Self {
+        Self {
+            version: Self::VERSION,
+            image_sequence,
+            frame_rate,
+            thumbnail: None,
+            snapshots: Vec::new(),
+            output_offset: (0, 0),
+            hardware_fps_limit: None,
+        }
+    }
+
+    fn from_bytes(bytes: &[u8], format: ProjectFormat) -> Option<Self> {
+        decode_project::<Self>(bytes, format)?.migrate()
+    }
+
+    fn migrate(mut self) -> Option<Self> {
+        if self.version > Self::VERSION {
+            return None;
+        }
+        self.version = Self::VERSION;
+        self.image_sequence.fill_missing_frame_data();
+        self.snapshots
+            .iter_mut()
+            .for_each(|(_, image_sequence)| image_sequence.fill_missing_frame_data());
+        Some(self)
+    }
+
+    fn to_bytes(&self, format: ProjectFormat) -> Result<Vec<u8>, String> {
+        match format {
+            ProjectFormat::Bson => bson::to_vec(self).map_err(|error| error.to_string()),
+            ProjectFormat::CompressedBson => {
+                let serialized = bson::to_vec(self).map_err(|error| error.to_string())?;
+                zstd::encode_all(serialized.as_slice(), 0).map_err(|error| error.to_string())
+            }
+            ProjectFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::ser::into_writer(self, &mut bytes).map_err(|error| error.to_string())?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn from_image(
+        path: &Path,
+        width: u8,
+        height: u8,
+        frame_rate: u16,
+        mode: ImportMode,
+    ) -> Option<Self> {
+        Self::from_dynamic_image(read_image(path)?, width, height, frame_rate, mode).ok()
+    }
+
+    fn from_dynamic_image(
+        image: DynamicImage,
+        width: u8,
+        height: u8,
+        frame_rate: u16,
+        mode: ImportMode,
+    ) -> Result<Self, String> {
+        let mut image_sequence = ImageSequence::new(width, height);
+        let [width_pixels, height_pixels] = image_sequence.get_dimensions_pixels();
+        let gray_image = dither_image(image, width_pixels, height_pixels, mode)?;
+        gray_image
+            .iter()
+            .zip(image_sequence.iter_pixels_mut(0).unwrap())
+            .for_each(|(&color, pixel)| {
+                *pixel = color != 0;
+            });
+        Ok(Self::new(image_sequence, frame_rate))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ProjectFormat {
+    Bson,
+    CompressedBson,
+    Cbor,
+}
+
+impl ProjectFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("bsonz") => Self::CompressedBson,
+            Some("cbor") => Self::Cbor,
+            _ => Self::Bson,
+        }
+    }
+}
+
+struct BatchConversion {
+    files: Vec<PathBuf>,
+    processed: usize,
+    failed: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct DefaultProjectSettings {
+    width: u8,
+    height: u8,
+    matrix_size: u8,
+    frame_rate: u16,
+}
+
+impl DefaultProjectSettings {
+    const STORAGE_KEY: &'static str = "default_project_settings";
+}
+
+impl Default for DefaultProjectSettings {
+    fn default() -> Self {
+        Self {
+            width: 4,
+            height: 4,
+            matrix_size: DEFAULT_MATRIX_SIZE,
+            frame_rate: 10,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ProtobufAnimation {
+    #[prost(uint32, tag = "1")]
+    width: u32,
+    #[prost(uint32, tag = "2")]
+    height: u32,
+    #[prost(uint32, tag = "3")]
+    frame_rate: u32,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    frames: Vec<Vec<u8>>,
+    #[prost(uint32, repeated, tag = "5")]
+    frame_durations: Vec<u32>,
+    #[prost(uint32, tag = "6")]
+    bits_per_pixel: u32,
+}
+
+#[derive(Serialize)]
+struct TimingMetadata {
+    frame_rate: u16,
+    frame_count: usize,
+    durations_ms: Vec<u32>,
 }
 
 struct NewFileDialog {
     show: bool,
     width: u8,
     height: u8,
+    matrix_size: u8,
     frame_rate: u16,
 }
 
+#[derive(Clone, Copy)]
+struct Selection {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Selection {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+
+    fn mask(&self, width: usize, height: usize) -> Vec<bool> {
+        (0..width * height)
+            .map(|i| self.contains(i % width, i / width))
+            .collect()
+    }
+
+    fn from_mask(mask: &[bool], width: usize) -> Option<Self> {
+        let [min_x, min_y, max_x, max_y] = mask
+            .iter()
+            .enumerate()
+            .filter(|&(_, &selected)| selected)
+            .map(|(i, _)| (i % width, i / width))
+            .fold(None, |bounds: Option<[usize; 4]>, (x, y)| {
+                Some(match bounds {
+                    None => [x, y, x, y],
+                    Some([min_x, min_y, max_x, max_y]) => {
+                        [min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)]
+                    }
+                })
+            })?;
+        Some(Self {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+        })
+    }
+}
+
+struct FrameHistoryEntry {
+    pixels: Vec<bool>,
+    levels: Vec<u8>,
+    group: Option<u64>,
+}
+
+struct FrameThumbnail {
+    bitmap: Vec<bool>,
+    levels: Vec<u8>,
+    color: [u8; 3],
+    background: [u8; 3],
+    texture: TextureHandle,
+}
+
+struct VuMeterDialog {
+    show: bool,
+    levels: String,
+    horizontal: bool,
+}
+
+struct ExportFramesDialog {
+    show: bool,
+    directory: Option<PathBuf>,
+    format: ImageFormat,
+    scale: u32,
+}
+
+struct FindPatternDialog {
+    show: bool,
+    width: usize,
+    height: usize,
+    pattern: Vec<bool>,
+    matches: Vec<(usize, usize, usize)>,
+}
+
 struct MainWindow {
     project: Project,
     current_file: Option<PathBuf>,
     scale: u16,
+    pixel_aspect: f32,
     current_frame: usize,
     show_grid: bool,
+    show_heatmap: bool,
+    show_rulers: bool,
     stoke_thickness: f32,
     onion_skin: bool,
     onion_opacity: f32,
+    onion_falloff: f32,
+    onion_depth: usize,
+    reference_image: Option<DynamicImage>,
+    reference_texture: Option<TextureHandle>,
+    reference_opacity: f32,
+    local_reference: bool,
+    css_selector: String,
+    gif_loop_count: u16,
+    video_frame_count: u32,
+    import_mode: ImportMode,
+    smooth_slide_pixels: usize,
+    smooth_slide_steps: usize,
+    pixel_sort_steps: usize,
+    shake_frames: usize,
+    shake_amplitude: u8,
+    grid_pattern_step: usize,
+    export_range: [usize; 2],
+    pixel_font: Option<PixelFont>,
+    text_tool_text: String,
+    emoji_text: String,
+    tween_method: TweenMethod,
     display_color: [u8; 3],
     new_file_dialog: NewFileDialog,
+    default_project_settings: DefaultProjectSettings,
+    recent_files: Vec<PathBuf>,
+    recent_thumbnails: HashMap<PathBuf, Option<TextureHandle>>,
+    show_welcome: bool,
+    show_shortcuts: bool,
+    show_similarity_matrix: bool,
+    show_hardware_profile: bool,
+    show_pip: bool,
+    pulse_thumbnails: bool,
+    frame_grid_view: bool,
+    multi_color_preview: Option<[[u8; 3]; 4]>,
+    selection: Option<Selection>,
+    draw_within_selection: bool,
+    pip_frame: usize,
+    pip_last_delta: Instant,
+    find_pattern_dialog: FindPatternDialog,
+    vu_meter_dialog: VuMeterDialog,
+    export_frames_dialog: ExportFramesDialog,
+    snapshot_name: Option<String>,
+    batch_rename_pattern: Option<String>,
     code_display: CodeDisplay,
+    code_var_name: String,
+    code_layer: Option<usize>,
+    code_template: CodeTemplate,
+    saved_templates: Vec<(String, CodeTemplate)>,
+    template_name: String,
+    include_frame_delays: bool,
+    include_frame_notes: bool,
+    byte_format: ByteFormat,
+    rename_frame: bool,
+    canvas_rect: Rect,
+    frame_strip_rects: Vec<Rect>,
+    frame_thumbnails: Vec<FrameThumbnail>,
+    last_saved_bitmaps: Vec<Vec<bool>>,
+    frame_histories: HashMap<usize, VecDeque<FrameHistoryEntry>>,
+    next_history_group: u64,
+    pixel_clipboard: Option<(Vec<bool>, usize)>,
+    paste_mode: PasteMode,
+    brush_level: u8,
+    frame_history_layout: (usize, usize),
     play: bool,
     last_frame_delta: Instant,
+    batch_conversion: Option<BatchConversion>,
+    selected_frames: BTreeSet<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ImportMode {
+    Stretch,
+    Fit,
+    Crop,
+}
+
+impl Display for ImportMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ImportMode::Stretch => "Stretch",
+                ImportMode::Fit => "Fit (letterbox)",
+                ImportMode::Crop => "Crop to fit",
+            }
+        )
+    }
+}
+
+impl ImportMode {
+    fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Stretch, Self::Fit, Self::Crop].into_iter()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PasteMode {
+    Replace,
+    Merge,
+}
+
+impl Display for PasteMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PasteMode::Replace => "Replace",
+                PasteMode::Merge => "Merge",
+            }
+        )
+    }
+}
+
+impl PasteMode {
+    fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Replace, Self::Merge].into_iter()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HardwareProfile {
+    Max7219,
+    Ht16k33,
+}
+
+impl Display for HardwareProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                HardwareProfile::Max7219 => "MAX7219",
+                HardwareProfile::Ht16k33 => "HT16K33",
+            }
+        )
+    }
+}
+
+impl HardwareProfile {
+    fn iter() -> impl ExactSizeIterator<Item = Self> {
+        [Self::Max7219, Self::Ht16k33].into_iter()
+    }
+
+    fn fps_limit(self) -> u16 {
+        match self {
+            HardwareProfile::Max7219 => 800,
+            HardwareProfile::Ht16k33 => 130,
+        }
+    }
 }
 
 #[derive(PartialEq)]
 enum CodeDisplay {
     SingleFrame,
     AllFrames,
+    CHeaderWithDefines,
+    Ht16k33,
+    Bitset,
+    Template,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct CodeTemplate {
+    header: String,
+    frame: String,
+    separator: String,
+    footer: String,
+}
+
+impl CodeTemplate {
+    fn presets() -> [(&'static str, Self); 3] {
+        [
+            (
+                "Arduino PROGMEM",
+                Self {
+                    header: String::from("const uint8_t {name}[{count}][{size}] PROGMEM = {\n"),
+                    frame: String::from("  { {bytes} }"),
+                    separator: String::from(",\n"),
+                    footer: String::from("\n};\n"),
+                },
+            ),
+            (
+                "MicroPython",
+                Self {
+                    header: String::from("{name} = [\n"),
+                    frame: String::from("    bytearray([{bytes}])"),
+                    separator: String::from(",\n"),
+                    footer: String::from("\n]\n"),
+                },
+            ),
+            (
+                "Rust const",
+                Self {
+                    header: String::from("pub const {name}: [[u8; {size}]; {count}] = [\n"),
+                    frame: String::from("    [{bytes}]"),
+                    separator: String::from(",\n"),
+                    footer: String::from("\n];\n"),
+                },
+            ),
+        ]
+    }
+
+    fn render(&self, name: &str, frames: &[Vec<String>]) -> String {
+        let size = frames.first().map_or(0, Vec::len);
+        let expand = |text: &str| {
+            text.replace("{name}", name)
+                .replace("{count}", &frames.len().to_string())
+                .replace("{size}", &size.to_string())
+        };
+        let body = frames
+            .iter()
+            .enumerate()
+            .map(|(idx, bytes)| {
+                expand(&self.frame)
+                    .replace("{index}", &idx.to_string())
+                    .replace("{bytes}", &bytes.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+        format!("{}{body}{}", expand(&self.header), expand(&self.footer))
+    }
 }
 
 impl App for MainWindow {
+    fn save(&mut self, storage: &mut dyn Storage) {
+        eframe::set_value(
+            storage,
+            DefaultProjectSettings::STORAGE_KEY,
+            &self.default_project_settings,
+        );
+        eframe::set_value(storage, Self::RECENT_FILES_KEY, &self.recent_files);
+        eframe::set_value(storage, Self::SAVED_TEMPLATES_KEY, &self.saved_templates);
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        let frame_time = Duration::from_nanos(1000000000 / u64::from(self.project.frame_rate));
+        let frame_time = self.frame_time(self.current_frame - 1);
         if self.play && self.last_frame_delta.elapsed() >= frame_time {
             self.last_frame_delta = Instant::now();
             self.current_frame =
@@ -106,44 +575,270 @@ impl App for MainWindow {
                 self.save_file();
             }
         });
+        if !ctx.wants_keyboard_input() {
+            ctx.input_mut(|input_state| {
+                if input_state.consume_shortcut(&Self::SELECT_ALL_SHORTCUT) {
+                    self.select_all_frames();
+                }
+            });
+            ctx.input_mut(|input_state| {
+                if input_state.consume_shortcut(&Self::FRAME_UNDO_SHORTCUT) {
+                    self.undo_frame();
+                }
+            });
+            ctx.input_mut(|input_state| {
+                if input_state.consume_shortcut(&Self::INVERT_FRAME_SHORTCUT) {
+                    self.invert_frame(self.current_frame - 1);
+                }
+            });
+            ctx.input_mut(|input_state| {
+                if input_state.consume_shortcut(&Self::GROW_SHORTCUT) {
+                    self.morph_selection(dilate_bitmap);
+                } else if input_state.consume_shortcut(&Self::SHRINK_SHORTCUT) {
+                    self.morph_selection(erode_bitmap);
+                }
+            });
+            ctx.input_mut(|input_state| {
+                if input_state.consume_shortcut(&Self::COPY_IMAGE_SHORTCUT) {
+                    self.copy_frame_to_clipboard(self.current_frame - 1);
+                } else if input_state.consume_shortcut(&Self::COPY_SHORTCUT) {
+                    self.copy_frame_pixels();
+                } else if input_state.consume_shortcut(&Self::PASTE_MIRRORED_HORIZONTALLY_SHORTCUT)
+                {
+                    self.paste_pixels(true, false);
+                } else if input_state.consume_shortcut(&Self::PASTE_MIRRORED_VERTICALLY_SHORTCUT) {
+                    self.paste_pixels(false, true);
+                } else if input_state.consume_shortcut(&Self::PASTE_SHORTCUT) {
+                    self.paste_pixels(false, false);
+                }
+            });
+            ctx.input_mut(|input_state| {
+                if input_state.consume_shortcut(&Self::RESET_VIEW_SHORTCUT) {
+                    self.scale = 1;
+                }
+            });
+        }
+        let frame_count = self.project.image_sequence.get_frame_count();
+        let frame_history_layout = (frame_count, self.project.image_sequence.get_active_layer());
+        if self.frame_history_layout != frame_history_layout {
+            self.frame_histories.clear();
+            self.frame_history_layout = frame_history_layout;
+        }
+        self.selected_frames.retain(|&idx| idx < frame_count);
         self.show_menu(ctx);
+        self.show_batch_conversion(ctx);
+        self.show_status_bar(ctx);
+        self.show_timeline(ctx);
+        self.show_find_pattern_dialog(ctx);
+        self.show_snapshot_dialog(ctx);
+        self.show_batch_rename_dialog(ctx);
+        self.show_vu_meter_dialog(ctx);
+        self.show_export_frames_dialog(ctx);
+        self.show_welcome_dialog(ctx);
+        self.show_shortcuts_dialog(ctx);
+        self.show_similarity_matrix_dialog(ctx);
+        self.show_hardware_profile_dialog(ctx);
         CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
-                self.show_painter(ui);
+                if self.frame_grid_view {
+                    self.show_frame_grid(ui);
+                } else {
+                    self.show_painter(ui);
+                }
                 ui.vertical(|ui| {
                     ui.horizontal(|ui| {
                         ui.label("Display color:");
                         ui.color_edit_button_srgb(&mut self.display_color);
                     });
                     ui.horizontal(|ui| {
+                        let mut mode = self.project.image_sequence.get_mode();
+                        CanvasMode::iter().for_each(|canvas_mode| {
+                            ui.radio_value(&mut mode, canvas_mode, canvas_mode.to_string());
+                        });
+                        self.project.image_sequence.set_mode(mode);
+                    });
+                    if self.project.image_sequence.get_mode() == CanvasMode::Grayscale4Bit {
+                        ui.add(
+                            DragValue::new(&mut self.brush_level)
+                                .clamp_range(1..=MAX_LEVEL)
+                                .prefix("Brightness: "),
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+                        let mut has_selection = self.selection.is_some();
+                        if ui.checkbox(&mut has_selection, "Selection").changed() {
+                            self.selection = has_selection.then_some(Selection {
+                                x: 0,
+                                y: 0,
+                                width,
+                                height,
+                            });
+                        }
+                        if let Some(selection) = &mut self.selection {
+                            ui.add(
+                                DragValue::new(&mut selection.x)
+                                    .clamp_range(0..=width - 1)
+                                    .prefix("x: "),
+                            );
+                            ui.add(
+                                DragValue::new(&mut selection.y)
+                                    .clamp_range(0..=height - 1)
+                                    .prefix("y: "),
+                            );
+                            ui.add(
+                                DragValue::new(&mut selection.width)
+                                    .clamp_range(1..=width)
+                                    .prefix("w: "),
+                            );
+                            ui.add(
+                                DragValue::new(&mut selection.height)
+                                    .clamp_range(1..=height)
+                                    .prefix("h: "),
+                            );
+                        }
+                    });
+                    ui.add_enabled(
+                        self.selection.is_some(),
+                        Checkbox::new(
+                            &mut self.draw_within_selection,
+                            "Draw within selection only",
+                        ),
+                    );
+                    ui.horizontal(|ui| {
+                        let frame_count = self.project.image_sequence.get_frame_count();
+                        let suffix = match self
+                            .project
+                            .image_sequence
+                            .get_frame_name(self.current_frame - 1)
+                        {
+                            Some(name) if !name.is_empty() => format!("/{frame_count} ({name})"),
+                            _ => format!("/{frame_count}"),
+                        };
                         ui.add(
                             DragValue::new(&mut self.current_frame)
-                                .clamp_range(1..=self.project.image_sequence.get_frame_count())
+                                .clamp_range(1..=frame_count)
                                 .prefix("Frame: ")
-                                .suffix(format!(
-                                    "/{}",
-                                    self.project.image_sequence.get_frame_count()
-                                )),
+                                .suffix(suffix),
                         );
+                        if ui
+                            .selectable_label(self.rename_frame, "✎")
+                            .on_hover_text("Rename frame")
+                            .clicked()
+                        {
+                            self.rename_frame = !self.rename_frame;
+                        }
                         if ui.button(if self.play { "Stop" } else { "Play" }).clicked() {
                             self.last_frame_delta = Instant::now();
                             self.play = !self.play;
                         }
                     });
+                    if self.rename_frame {
+                        if let Some(name) = self
+                            .project
+                            .image_sequence
+                            .get_frame_name_mut(self.current_frame - 1)
+                        {
+                            ui.add(TextEdit::singleline(name).hint_text("Frame name"));
+                        }
+                    }
+                    if let Some(note) = self
+                        .project
+                        .image_sequence
+                        .get_frame_note_mut(self.current_frame - 1)
+                    {
+                        ui.add(
+                            TextEdit::multiline(note)
+                                .desired_rows(2)
+                                .hint_text("Frame notes"),
+                        );
+                    }
+                    if let Some(background) = self
+                        .project
+                        .image_sequence
+                        .get_background_color_mut(self.current_frame - 1)
+                    {
+                        ui.horizontal(|ui| {
+                            let mut custom = background.is_some();
+                            if ui.checkbox(&mut custom, "Background").changed() {
+                                *background = custom.then_some(Self::BACKGROUND_COLOR);
+                            }
+                            if let Some(color) = background {
+                                ui.color_edit_button_srgb(color);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Overrides the background colour for this frame");
+                    }
+                    if let Some(duration) = self
+                        .project
+                        .image_sequence
+                        .get_frame_duration_mut(self.current_frame - 1)
+                    {
+                        ui.add(
+                            DragValue::new(duration)
+                                .clamp_range(Self::FRAME_DURATION_RANGE)
+                                .prefix("Duration: ")
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text("0 uses the project frame rate");
+                    }
+                    let mut keyframe = self
+                        .project
+                        .image_sequence
+                        .is_keyframe(self.current_frame - 1);
+                    if ui.checkbox(&mut keyframe, "Keyframe").changed() {
+                        self.project
+                            .image_sequence
+                            .toggle_keyframe(self.current_frame - 1);
+                    }
+                    let locked = self
+                        .project
+                        .image_sequence
+                        .is_locked(self.current_frame - 1);
+                    if ui
+                        .selectable_label(
+                            locked,
+                            if locked {
+                                "🔒 Locked"
+                            } else {
+                                "🔓 Unlocked"
+                            },
+                        )
+                        .on_hover_text("Locked frames cannot be edited, cleared or deleted")
+                        .clicked()
+                    {
+                        self.project
+                            .image_sequence
+                            .toggle_lock(self.current_frame - 1);
+                    }
                     ui.horizontal(|ui| {
                         if ui.button("Add frame").clicked() {
                             self.project.image_sequence.add_frame();
                             self.current_frame = self.project.image_sequence.get_frame_count();
                         }
                         if ui.button("Insert frame").clicked() {
-                            self.project
-                                .image_sequence
-                                .insert_frame(self.current_frame - 1);
+                            self.target_frames().into_iter().for_each(|idx| {
+                                self.project.image_sequence.insert_frame(idx);
+                            });
+                            self.selected_frames.clear();
                         }
                         if ui.button("Duplicate frame").clicked() {
+                            self.target_frames().into_iter().for_each(|idx| {
+                                self.project.image_sequence.duplicate_frame(idx);
+                            });
+                            self.selected_frames.clear();
+                        }
+                        if ui
+                            .button("Smart duplicate")
+                            .on_hover_text("Duplicate the current frame and switch to the copy")
+                            .clicked()
+                        {
                             self.project
                                 .image_sequence
                                 .duplicate_frame(self.current_frame - 1);
+                            self.current_frame += 1;
+                            self.selected_frames.clear();
                         }
                     });
                     ui.horizontal(|ui| {
@@ -163,37 +858,58 @@ impl App for MainWindow {
                     });
                     ui.horizontal(|ui| {
                         if ui.button("Delete frame").clicked() {
-                            self.project
-                                .image_sequence
-                                .delete_frame(self.current_frame - 1);
-                            if self.project.image_sequence.get_frame_count() == 0 {
-                                self.project.image_sequence.add_frame();
-                            } else if self.project.image_sequence.get_frame_count()
-                                == self.current_frame - 1
-                            {
-                                self.current_frame -= 1;
-                            }
+                            self.delete_target_frames();
                         }
                         if ui.button("Clear frame").clicked() {
-                            self.project
-                                .image_sequence
-                                .clear_frame(self.current_frame - 1);
+                            self.target_frames().into_iter().for_each(|idx| {
+                                self.project.image_sequence.clear_frame(idx);
+                            });
                         }
                     });
+                    if !self.selected_frames.is_empty() {
+                        ui.label(format!("{} frames selected", self.selected_frames.len()));
+                    }
                 });
             });
+            self.update_frame_thumbnails(ctx);
+            self.show_frame_strip(ui);
+            let mut confirm_new = false;
+            let mut new_from_clipboard = false;
             Window::new("New")
                 .open(&mut self.new_file_dialog.show)
                 .show(ctx, |ui| {
-                    ui.label("Width:");
-                    ui.horizontal(|ui| {
-                        ui.add(DragValue::new(&mut self.new_file_dialog.width).clamp_range(1..=8));
-                        ui.label(format!(" × 8 = {}", self.new_file_dialog.width * 8));
-                    });
-                    ui.label("Height:");
-                    ui.horizontal(|ui| {
-                        ui.add(DragValue::new(&mut self.new_file_dialog.height).clamp_range(1..=8));
-                        ui.label(format!(" × 8 = {}", self.new_file_dialog.height * 8));
+                    ui.label("Matrix size:");
+                    ui.add(
+                        DragValue::new(&mut self.new_file_dialog.matrix_size)
+                            .clamp_range(Self::MATRIX_SIZE_RANGE)
+                            .suffix(" px"),
+                    )
+                    .on_hover_text("Pixels along one side of a single LED matrix module");
+                    let matrix_size = u16::from(self.new_file_dialog.matrix_size);
+                    [
+                        ("Width:", &mut self.new_file_dialog.width),
+                        ("Height:", &mut self.new_file_dialog.height),
+                    ]
+                    .into_iter()
+                    .for_each(|(label, matrices)| {
+                        ui.label(label);
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                DragValue::new(matrices)
+                                    .clamp_range(1..=8)
+                                    .suffix(" px")
+                                    .custom_formatter(|matrices, _| {
+                                        (matrices * f64::from(matrix_size)).to_string()
+                                    })
+                                    .custom_parser(|text| {
+                                        let pixels = text.trim().parse::<u16>().ok()?;
+                                        (pixels % matrix_size == 0)
+                                            .then(|| f64::from(pixels / matrix_size))
+                                    }),
+                            )
+                            .on_hover_text(format!("A multiple of {matrix_size} px"));
+                            ui.label(format!("= {matrices} × {matrix_size}"));
+                        });
                     });
                     ui.label("Frame rate:");
                     ui.add(
@@ -201,19 +917,35 @@ impl App for MainWindow {
                             .clamp_range(Self::FPS_RANGE),
                     );
                     ui.vertical_centered_justified(|ui| {
-                        if ui.button("Confirm").clicked() {
-                            self.current_file = None;
-                            self.current_frame = 1;
-                            self.project = Project {
-                                image_sequence: ImageSequence::new(
-                                    self.new_file_dialog.width,
-                                    self.new_file_dialog.height,
-                                ),
+                        if ui.button("Set as default").clicked() {
+                            self.default_project_settings = DefaultProjectSettings {
+                                width: self.new_file_dialog.width,
+                                height: self.new_file_dialog.height,
+                                matrix_size: self.new_file_dialog.matrix_size,
                                 frame_rate: self.new_file_dialog.frame_rate,
                             };
                         }
+                        confirm_new = ui.button("Confirm").clicked();
+                        new_from_clipboard = ui.button("From clipboard image").clicked();
                     });
                 });
+            if confirm_new {
+                self.show_welcome = false;
+                self.replace_project(
+                    Project::new(
+                        ImageSequence::with_matrix_size(
+                            self.new_file_dialog.width,
+                            self.new_file_dialog.height,
+                            self.new_file_dialog.matrix_size,
+                        ),
+                        self.new_file_dialog.frame_rate,
+                    ),
+                    None,
+                );
+            }
+            if new_from_clipboard {
+                self.new_from_clipboard();
+            }
             ui.collapsing("Code", |ui| {
                 ui.radio_value(
                     &mut self.code_display,
@@ -221,59 +953,224 @@ impl App for MainWindow {
                     "Current frame",
                 );
                 ui.radio_value(&mut self.code_display, CodeDisplay::AllFrames, "All frames");
+                ui.radio_value(
+                    &mut self.code_display,
+                    CodeDisplay::CHeaderWithDefines,
+                    "C header (current frame)",
+                );
+                ui.radio_value(
+                    &mut self.code_display,
+                    CodeDisplay::Ht16k33,
+                    "HT16K33 (current frame)",
+                );
+                ui.radio_value(
+                    &mut self.code_display,
+                    CodeDisplay::Bitset,
+                    "C++ std::bitset (current frame)",
+                );
+                ui.radio_value(
+                    &mut self.code_display,
+                    CodeDisplay::Template,
+                    "Template (all frames)",
+                );
+                if self.code_display == CodeDisplay::Template {
+                    self.show_code_template(ui);
+                }
+                if self.code_display == CodeDisplay::AllFrames {
+                    ui.checkbox(&mut self.include_frame_delays, "Include frame delays");
+                    ui.checkbox(&mut self.include_frame_notes, "Include frame notes");
+                }
+                if self.code_display == CodeDisplay::SingleFrame {
+                    let layer_count = self.project.image_sequence.get_layer_count();
+                    self.code_layer = self.code_layer.filter(|&layer| layer < layer_count);
+                    ComboBox::from_label("Layer")
+                        .selected_text(match self.code_layer {
+                            Some(layer) => self.project.image_sequence.get_layer_name(layer),
+                            None => "All layers",
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.code_layer, None, "All layers");
+                            (0..layer_count).for_each(|layer| {
+                                ui.selectable_value(
+                                    &mut self.code_layer,
+                                    Some(layer),
+                                    self.project.image_sequence.get_layer_name(layer),
+                                );
+                            });
+                        });
+                }
+                if self.code_display == CodeDisplay::CHeaderWithDefines
+                    || self.code_display == CodeDisplay::Ht16k33
+                    || self.code_display == CodeDisplay::Bitset
+                    || self.code_display == CodeDisplay::Template
+                    || self.code_display == CodeDisplay::AllFrames && self.include_frame_delays
+                {
+                    ui.horizontal(|ui| {
+                        ui.label("Variable name:");
+                        ui.text_edit_singleline(&mut self.code_var_name);
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Output offset:");
+                    ui.add(DragValue::new(&mut self.project.output_offset.0).prefix("dx: "));
+                    ui.add(DragValue::new(&mut self.project.output_offset.1).prefix("dy: "));
+                })
+                .response
+                .on_hover_text(
+                    "Shifts generated code and byte exports without changing the frames",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Radix:");
+                    ByteFormat::iter().for_each(|byte_format| {
+                        ui.radio_value(&mut self.byte_format, byte_format, byte_format.to_string());
+                    });
+                });
+                if ui.button("Save code to file…").clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .add_filter("Header file", &["h"])
+                        .add_filter("Text file", &["txt"])
+                        .save_file()
+                    {
+                        self.save_code(&path);
+                    }
+                }
                 ScrollArea::vertical().show(ui, |ui| {
                     ui.add(
-                        TextEdit::multiline(&mut match self.code_display {
-                            CodeDisplay::SingleFrame => self
-                                .project
-                                .image_sequence
-                                .get_frame_as_string(self.current_frame - 1),
-                            CodeDisplay::AllFrames => {
-                                self.project.image_sequence.get_sequence_as_string()
-                            }
-                        })
-                        .code_editor()
-                        .desired_width(f32::INFINITY),
+                        TextEdit::multiline(&mut self.generated_code())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
                     );
                 });
             });
+            ui.collapsing("Layers", |ui| {
+                self.show_layers(ui);
+            });
+            ui.collapsing("Statistics", |ui| {
+                self.show_statistics(ui);
+            });
         });
-        if self.play {
+        self.show_pip_preview(ctx);
+        self.show_multi_color_preview(ctx);
+        self.handle_dropped_files(ctx);
+        if self.play || self.show_pip {
             ctx.request_repaint();
         }
     }
 }
 
-#[derive(Clone, Copy)]
-pub enum Direction {
-    Top,
-    Left,
-    Bottom,
-    Right,
-}
+impl MainWindow {
+    fn new(
+        default_project_settings: DefaultProjectSettings,
+        recent_files: Vec<PathBuf>,
+        saved_templates: Vec<(String, CodeTemplate)>,
+    ) -> Self {
+        let project = Project::new(ImageSequence::new(4, 4), 10);
+        Self {
+            last_saved_bitmaps: project.image_sequence.iter_frames().collect(),
+            project,
+            current_file: None,
+            scale: 1,
+            pixel_aspect: 1.0,
+            current_frame: 1,
+            show_grid: false,
+            show_heatmap: false,
+            show_rulers: false,
+            stoke_thickness: 1.0,
+            onion_skin: false,
+            onion_opacity: 0.05,
+            onion_falloff: 0.0,
+            onion_depth: 1,
+            reference_image: None,
+            reference_texture: None,
+            reference_opacity: 0.5,
+            local_reference: false,
+            css_selector: String::from(".led-matrix"),
+            gif_loop_count: 0,
+            video_frame_count: 30,
+            import_mode: ImportMode::Stretch,
+            smooth_slide_pixels: 8,
+            smooth_slide_steps: 5,
+            pixel_sort_steps: 8,
+            shake_frames: 8,
+            shake_amplitude: 1,
+            grid_pattern_step: 2,
+            export_range: [1, 1],
+            pixel_font: None,
+            text_tool_text: String::new(),
+            emoji_text: String::from("☺"),
+            tween_method: TweenMethod {
+                steps: 3,
+                dither: DitherMethod::Ordered,
+            },
+            display_color: [0xFF, 0x00, 0x00],
+            snapshot_name: None,
+            batch_rename_pattern: None,
+            find_pattern_dialog: FindPatternDialog {
+                show: false,
+                width: 3,
+                height: 3,
+                pattern: vec![false; 9],
+                matches: Vec::new(),
+            },
+            vu_meter_dialog: VuMeterDialog {
+                show: false,
+                levels: String::new(),
+                horizontal: false,
+            },
+            export_frames_dialog: ExportFramesDialog {
+                show: false,
+                directory: None,
+                format: ImageFormat::Png,
+                scale: 1,
+            },
+            new_file_dialog: NewFileDialog {
+                show: false,
+                width: default_project_settings.width,
+                height: default_project_settings.height,
+                matrix_size: default_project_settings.matrix_size,
+                frame_rate: default_project_settings.frame_rate,
+            },
+            default_project_settings,
+            recent_files,
+            recent_thumbnails: HashMap::new(),
+            show_welcome: true,
+            show_shortcuts: false,
+            show_similarity_matrix: false,
+            show_hardware_profile: false,
+            show_pip: false,
+            pulse_thumbnails: false,
+            frame_grid_view: false,
+            multi_color_preview: None,
+            selection: None,
+            draw_within_selection: false,
+            pip_frame: 0,
+            pip_last_delta: Instant::now(),
+            code_display: CodeDisplay::SingleFrame,
+            code_var_name: String::from("image"),
+            code_layer: None,
+            code_template: CodeTemplate::presets()[0].1.clone(),
+            saved_templates,
+            template_name: String::new(),
+            include_frame_delays: false,
+            include_frame_notes: false,
+            byte_format: ByteFormat::Hex04,
+            rename_frame: false,
+            canvas_rect: Rect::NOTHING,
+            frame_strip_rects: Vec::new(),
+            frame_thumbnails: Vec::new(),
+            frame_histories: HashMap::new(),
+            next_history_group: 0,
+            pixel_clipboard: None,
+            paste_mode: PasteMode::Replace,
+            brush_level: MAX_LEVEL,
+            frame_history_layout: (0, 0),
+            play: false,
+            last_frame_delta: Instant::now(),
+            batch_conversion: None,
+            selected_frames: BTreeSet::new(),
+        }
+    }
 
-impl Display for Direction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Direction::Top => "Top",
-                Direction::Left => "Left",
-                Direction::Bottom => "Bottom",
-                Direction::Right => "Right",
-            }
-        )
-    }
-}
-
-impl Direction {
-    fn iter() -> impl ExactSizeIterator<Item = Self> {
-        [Self::Top, Self::Left, Self::Bottom, Self::Right].into_iter()
-    }
-}
-
-impl MainWindow {
     const OPEN_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
         modifiers: Modifiers::CTRL,
         key: Key::O,
@@ -284,15 +1181,157 @@ impl MainWindow {
         key: Key::S,
     };
 
+    const SELECT_ALL_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::CTRL,
+        key: Key::A,
+    };
+
+    const FRAME_UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers {
+            shift: true,
+            ..Modifiers::CTRL
+        },
+        key: Key::Z,
+    };
+
+    const INVERT_FRAME_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::CTRL,
+        key: Key::I,
+    };
+
+    // egui 0.21 has no bracket keys, so grow and shrink use plus and minus.
+    const GROW_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::CTRL,
+        key: Key::PlusEquals,
+    };
+
+    const SHRINK_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::CTRL,
+        key: Key::Minus,
+    };
+
+    const COPY_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::CTRL,
+        key: Key::C,
+    };
+
+    const COPY_IMAGE_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers {
+            shift: true,
+            ..Modifiers::CTRL
+        },
+        key: Key::C,
+    };
+
+    const PASTE_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::CTRL,
+        key: Key::V,
+    };
+
+    const PASTE_MIRRORED_HORIZONTALLY_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers {
+            shift: true,
+            ..Modifiers::CTRL
+        },
+        key: Key::V,
+    };
+
+    const PASTE_MIRRORED_VERTICALLY_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers {
+            alt: true,
+            ..Modifiers::CTRL
+        },
+        key: Key::V,
+    };
+
+    const RESET_VIEW_SHORTCUT: KeyboardShortcut = KeyboardShortcut {
+        modifiers: Modifiers::NONE,
+        key: Key::Home,
+    };
+
+    const FRAME_HISTORY_LENGTH: usize = 32;
+
+    const DIRTY_MARKER_RADIUS: f32 = 3.0;
+
+    const THUMBNAIL_PULSE_DURATION: Duration = Duration::from_millis(120);
+
+    const RECENT_FILES_KEY: &'static str = "recent_files";
+
+    const SAVED_TEMPLATES_KEY: &'static str = "saved_templates";
+
+    const RECENT_FILES_LENGTH: usize = 8;
+
+    const RULER_SIZE: f32 = 16.0;
+
+    const TIMELINE_PIXELS_PER_MS: f32 = 0.2;
+
+    const TIMELINE_HEIGHT: f32 = 32.0;
+
+    const HTML_VIEWER_SCALE: u16 = 8;
+
+    const THUMBNAIL_SIZE: u32 = 32;
+
+    const BACKGROUND_COLOR: [u8; 3] = [0x00, 0x00, 0x00];
+
+    const FRAME_GRID_COLUMNS: usize = 6;
+
+    const FRAME_GRID_CELL_SIZE: f32 = 96.0;
+
+    const MULTI_COLOR_PREVIEW_COLORS: [[u8; 3]; 4] = [
+        [0xFF, 0x00, 0x00],
+        [0x00, 0xFF, 0x00],
+        [0xFF, 0xB0, 0x00],
+        [0x40, 0x80, 0xFF],
+    ];
+
+    const MULTI_COLOR_PREVIEW_GAP: f32 = 4.0;
+
+    const EXPORT_SCALE_RANGE: RangeInclusive<u32> = 1..=64;
+
+    const SVG_USE_THRESHOLD: usize = 256;
+
     const FPS_RANGE: RangeInclusive<u16> = 1..=60;
 
+    const MATRIX_SIZE_RANGE: RangeInclusive<u8> = 1..=32;
+
+    const FRAME_DURATION_RANGE: RangeInclusive<u32> = 0..=60000;
+
+    const VIDEO_FRAME_COUNT_RANGE: RangeInclusive<u32> = 1..=1000;
+
+    const ONION_DEPTH_RANGE: RangeInclusive<usize> = 1..=8;
+
+    const ONION_HUE_STEP_DEGREES: f32 = 30.0;
+
     fn open_file(&mut self) {
         let Some(path) = FileDialog::new()
+            .add_filter("Project file", &["bson", "bsonz", "cbor"])
             .add_filter("BSON file", &["bson"])
+            .add_filter("Compressed BSON file", &["bsonz"])
+            .add_filter("CBOR file", &["cbor"])
             .pick_file() else {
             return;
         };
 
+        self.open_path(path);
+    }
+
+    fn revert(&mut self) {
+        let Some(path) = self.current_file.clone() else {
+            return;
+        };
+
+        if !MessageDialog::new()
+            .set_description("Discard unsaved changes?")
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+        {
+            return;
+        }
+
+        self.open_path(path);
+    }
+
+    fn open_path(&mut self, path: PathBuf) {
         let Ok(file_bytes) = fs::read(&path) else {
             MessageDialog::new()
                 .set_description(&format!("Could not open file {} for reading", path.display()))
@@ -300,60 +1339,395 @@ impl MainWindow {
             return;
         };
 
-        let Ok(project) = bson::from_slice(&file_bytes) else {
+        let (project_bytes, checksum_valid) = split_checksum(&file_bytes);
+        if checksum_valid == Some(false)
+            && !MessageDialog::new()
+                .set_title("Checksum mismatch")
+                .set_description(&format!(
+                    "The checksum of {} does not match, the file may be corrupted.\n\
+                     Load anyway (unsafe)?",
+                    path.display()
+                ))
+                .set_buttons(MessageButtons::YesNo)
+                .show()
+        {
+            return;
+        }
+
+        let Some(project) = Project::from_bytes(project_bytes, ProjectFormat::from_path(&path))
+        else {
             MessageDialog::new()
                 .set_description(&format!("Could not parse file {}", path.display()))
                 .show();
             return;
         };
 
-        self.current_file = Some(path);
-        self.current_frame = 1;
+        self.add_recent_file(&path);
+        self.replace_project(project, Some(path));
+    }
+
+    fn replace_project(&mut self, project: Project, current_file: Option<PathBuf>) {
+        self.current_frame = self
+            .current_frame
+            .min(project.image_sequence.get_frame_count());
+        self.last_saved_bitmaps = project.image_sequence.iter_frames().collect();
+        self.frame_histories.clear();
+        self.selected_frames.clear();
         self.project = project;
+        self.current_file = current_file;
     }
 
-    fn write_file(&self, path: &Path) -> bool {
-        let serialized = match bson::to_vec(&self.project) {
-            Ok(serialized) => serialized,
+    fn import_from_clipboard(&mut self) {
+        let Ok(text) = Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) else {
+            MessageDialog::new()
+                .set_description("Could not read text from the clipboard")
+                .show();
+            return;
+        };
+
+        let Ok(project_bytes) = general_purpose::STANDARD.decode(text.trim()) else {
+            MessageDialog::new()
+                .set_description("Clipboard does not contain base64 data")
+                .show();
+            return;
+        };
+
+        let Some(project) = Project::from_bytes(&project_bytes, ProjectFormat::Bson) else {
+            MessageDialog::new()
+                .set_description("Could not parse project from the clipboard")
+                .show();
+            return;
+        };
+
+        if MessageDialog::new()
+            .set_description("Save the current project before replacing it?")
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+            && !self.save_file()
+        {
+            return;
+        }
+
+        self.replace_project(project, None);
+    }
+
+    fn extract_frame_as_new_project(&mut self, idx: usize) {
+        if MessageDialog::new()
+            .set_description("Save the current project before replacing it?")
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+            && !self.save_file()
+        {
+            return;
+        }
+
+        self.replace_project(
+            Project::new(
+                self.project.image_sequence.subrange(idx, idx),
+                self.project.frame_rate,
+            ),
+            None,
+        );
+    }
+
+    fn new_from_clipboard(&mut self) {
+        let Some(image) = Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_image())
+            .ok()
+            .and_then(|image_data| {
+                RgbaImage::from_raw(
+                    u32::try_from(image_data.width).ok()?,
+                    u32::try_from(image_data.height).ok()?,
+                    image_data.bytes.into_owned(),
+                )
+            })
+        else {
+            MessageDialog::new()
+                .set_description("Could not read an image from the clipboard")
+                .show();
+            return;
+        };
+
+        let project = match Project::from_dynamic_image(
+            DynamicImage::ImageRgba8(image),
+            self.new_file_dialog.width,
+            self.new_file_dialog.height,
+            self.new_file_dialog.frame_rate,
+            self.import_mode,
+        ) {
+            Ok(project) => project,
             Err(error) => {
-                MessageDialog::new()
-                    .set_description(&format!("Could not serialize project, error: {error}"))
-                    .show();
-                return false;
+                MessageDialog::new().set_description(&error).show();
+                return;
             }
         };
 
-        if fs::write(path, serialized).is_err() {
+        self.show_welcome = false;
+        self.replace_project(project, None);
+    }
+
+    fn write_file(&mut self, path: &Path) -> bool {
+        self.project.thumbnail = self.render_thumbnail(0);
+        if !write_project(&self.project, path) {
+            return false;
+        }
+        self.last_saved_bitmaps = self.project.image_sequence.iter_frames().collect();
+        true
+    }
+
+    fn generated_code(&self) -> String {
+        let image_sequence = self.output_sequence();
+        let code = match self.code_display {
+            CodeDisplay::SingleFrame => match self.code_layer {
+                Some(layer) => image_sequence.get_frame_layer_as_string(
+                    self.current_frame - 1,
+                    layer,
+                    self.byte_format,
+                ),
+                None => image_sequence
+                    .get_frame_as_string_formatted(self.current_frame - 1, self.byte_format),
+            },
+            CodeDisplay::AllFrames if self.include_frame_delays => image_sequence
+                .get_sequence_as_c_with_durations(
+                    &self.code_var_name,
+                    self.project.frame_rate,
+                    self.byte_format,
+                    self.include_frame_notes,
+                ),
+            CodeDisplay::AllFrames => image_sequence
+                .get_sequence_as_string_formatted(self.byte_format, self.include_frame_notes),
+            CodeDisplay::CHeaderWithDefines => {
+                image_sequence.get_frame_as_c_header(self.current_frame - 1, &self.code_var_name)
+            }
+            CodeDisplay::Ht16k33 => image_sequence.get_frame_as_ht16k33_code(
+                self.current_frame - 1,
+                &self.code_var_name,
+                self.byte_format,
+            ),
+            CodeDisplay::Bitset => {
+                image_sequence.get_frame_as_bitset(self.current_frame - 1, &self.code_var_name)
+            }
+            CodeDisplay::Template => self.code_template.render(
+                &self.code_var_name,
+                &(0..image_sequence.get_frame_count())
+                    .map(|idx| {
+                        image_sequence
+                            .get_bytes(idx)
+                            .map(|byte| self.byte_format.format_byte(byte))
+                            .collect()
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        match image_sequence.get_background_colors_as_c(&self.code_var_name, Self::BACKGROUND_COLOR)
+        {
+            Some(backgrounds) if self.code_display == CodeDisplay::AllFrames => {
+                format!("{code}\n{backgrounds}")
+            }
+            _ => code,
+        }
+    }
+
+    fn show_code_template(&mut self, ui: &mut Ui) {
+        let presets = CodeTemplate::presets();
+        let selected_name = presets
+            .iter()
+            .map(|(name, template)| (*name, template))
+            .chain(
+                self.saved_templates
+                    .iter()
+                    .map(|(name, template)| (name.as_str(), template)),
+            )
+            .find(|(_, template)| **template == self.code_template)
+            .map_or("Custom…", |(name, _)| name)
+            .to_owned();
+        ComboBox::from_label("Templates")
+            .selected_text(selected_name)
+            .show_ui(ui, |ui| {
+                presets.iter().for_each(|(name, template)| {
+                    if ui.selectable_label(false, *name).clicked() {
+                        self.code_template = template.clone();
+                    }
+                });
+                if !self.saved_templates.is_empty() {
+                    ui.separator();
+                }
+                self.saved_templates.iter().for_each(|(name, template)| {
+                    if ui.selectable_label(false, name).clicked() {
+                        self.code_template = template.clone();
+                    }
+                });
+            });
+        Grid::new("code_template").num_columns(2).show(ui, |ui| {
+            ui.label("Header:");
+            ui.text_edit_multiline(&mut self.code_template.header);
+            ui.end_row();
+            ui.label("Frame:");
+            ui.text_edit_multiline(&mut self.code_template.frame);
+            ui.end_row();
+            ui.label("Separator:");
+            ui.text_edit_singleline(&mut self.code_template.separator);
+            ui.end_row();
+            ui.label("Footer:");
+            ui.text_edit_multiline(&mut self.code_template.footer);
+            ui.end_row();
+        });
+        ui.label("Placeholders: {name}, {count}, {size}, {index}, {bytes}");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.template_name);
+            if ui
+                .add_enabled(
+                    !self.template_name.trim().is_empty(),
+                    Button::new("Save template"),
+                )
+                .clicked()
+            {
+                let name = self.template_name.trim().to_owned();
+                match self
+                    .saved_templates
+                    .iter_mut()
+                    .find(|(saved_name, _)| *saved_name == name)
+                {
+                    Some((_, template)) => *template = self.code_template.clone(),
+                    None => self
+                        .saved_templates
+                        .push((name, self.code_template.clone())),
+                }
+                self.template_name.clear();
+            }
+        });
+    }
+
+    fn output_sequence(&self) -> Cow<'_, ImageSequence> {
+        match self.project.output_offset {
+            (0, 0) => Cow::Borrowed(&self.project.image_sequence),
+            (dx, dy) => Cow::Owned(self.project.image_sequence.with_output_offset(dx, dy)),
+        }
+    }
+
+    fn save_code(&self, path: &Path) {
+        if fs::write(path, self.generated_code()).is_err() {
             MessageDialog::new()
                 .set_description(&format!(
                     "Could not open file {} for writing",
                     path.display()
                 ))
                 .show();
-            return false;
         }
+    }
 
-        true
+    fn export_subrange(&self, start: usize, end: usize, path: &Path) {
+        let mut project = Project::new(
+            self.project.image_sequence.subrange(start, end),
+            self.project.frame_rate,
+        );
+        project.thumbnail = self.render_thumbnail(start);
+        write_project(&project, path);
     }
 
-    fn save_file(&mut self) {
-        if let Some(current_file) = &self.current_file {
-            self.write_file(current_file);
+    fn save_file(&mut self) -> bool {
+        if let Some(current_file) = self.current_file.clone() {
+            self.write_file(&current_file)
         } else {
-            self.save_file_as();
+            self.save_file_as()
         }
     }
 
-    fn save_file_as(&mut self) {
+    fn save_file_as(&mut self) -> bool {
         let Some(path) = FileDialog::new()
             .add_filter("BSON file", &["bson"])
+            .add_filter("Compressed BSON file", &["bsonz"])
+            .add_filter("CBOR file", &["cbor"])
             .save_file() else {
-            return;
+            return false;
         };
 
-        if self.write_file(&path) {
-            self.current_file = Some(path);
+        if !self.write_file(&path) {
+            return false;
+        }
+        self.add_recent_file(&path);
+        self.current_file = Some(path);
+        true
+    }
+
+    fn add_recent_file(&mut self, path: &Path) {
+        self.recent_thumbnails.remove(path);
+        self.recent_files.retain(|recent_file| recent_file != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(Self::RECENT_FILES_LENGTH);
+    }
+
+    fn show_welcome_dialog(&mut self, ctx: &Context) {
+        if self.current_file.is_some() {
+            self.show_welcome = false;
+        }
+        if !self.show_welcome {
+            return;
+        }
+
+        self.recent_files.iter().for_each(|recent_file| {
+            if !self.recent_thumbnails.contains_key(recent_file) {
+                self.recent_thumbnails.insert(
+                    recent_file.clone(),
+                    read_project_thumbnail(ctx, recent_file),
+                );
+            }
+        });
+
+        let mut show = true;
+        let mut new_project = false;
+        let mut open = false;
+        let mut opened_recent = None;
+        Window::new("Welcome")
+            .open(&mut show)
+            .collapsible(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.heading("maturski");
+                ui.vertical_centered_justified(|ui| {
+                    new_project = ui.button("New project").clicked();
+                    open = ui.button("Open…").clicked();
+                });
+                if !self.recent_files.is_empty() {
+                    ui.separator();
+                    ui.label("Recent files:");
+                    self.recent_files.iter().for_each(|recent_file| {
+                        ui.horizontal(|ui| {
+                            let thumbnail_clicked = match self.recent_thumbnails.get(recent_file) {
+                                Some(Some(texture)) => ui
+                                    .add(ImageButton::new(
+                                        texture.id(),
+                                        Vec2::splat(Self::THUMBNAIL_SIZE as f32),
+                                    ))
+                                    .clicked(),
+                                _ => false,
+                            };
+                            if ui.link(recent_file.display().to_string()).clicked()
+                                || thumbnail_clicked
+                            {
+                                opened_recent = Some(recent_file.clone());
+                            }
+                        });
+                    });
+                }
+            });
+
+        if new_project {
+            self.new_file_dialog.show = true;
+            show = false;
+        }
+        if open {
+            self.open_file();
+        }
+        if let Some(path) = opened_recent {
+            self.open_path(path);
         }
+        self.show_welcome = show;
+    }
+
+    fn get_pixel_size(&self) -> Vec2 {
+        let scale = f32::from(self.scale);
+        Vec2::new(scale, scale * self.pixel_aspect)
     }
 
     fn render_frame(
@@ -364,13 +1738,24 @@ impl MainWindow {
         color: Color32,
     ) {
         if let Some(pixels) = self.project.image_sequence.iter_pixels(frame_idx) {
-            let scale = usize::from(self.scale);
-            let scale_vec2 = Vec2::new(self.scale.into(), self.scale.into());
+            let pixel_size = self.get_pixel_size();
+            let mode = self.project.image_sequence.get_mode();
             pixels.filter(|&(_, _, pixel)| pixel).for_each(|(x, y, _)| {
-                let position_scaled =
-                    Pos2::new((x * scale) as f32, (y * scale) as f32) + painter_top_left.to_vec2();
+                let position_scaled = Pos2::new(x as f32 * pixel_size.x, y as f32 * pixel_size.y)
+                    + painter_top_left.to_vec2();
+                let color = match mode {
+                    CanvasMode::Monochrome => color,
+                    CanvasMode::Grayscale4Bit => {
+                        let level = self
+                            .project
+                            .image_sequence
+                            .get_level(x, y, frame_idx)
+                            .unwrap_or(MAX_LEVEL);
+                        color.linear_multiply(f32::from(level) / f32::from(MAX_LEVEL))
+                    }
+                };
                 painter.rect_filled(
-                    Rect::from_min_size(position_scaled, scale_vec2),
+                    Rect::from_min_size(position_scaled, pixel_size),
                     Rounding::none(),
                     color,
                 );
@@ -380,54 +1765,162 @@ impl MainWindow {
 
     fn show_painter(&mut self, ui: &mut Ui) {
         let [width_pixels, height_pixels] = self.project.image_sequence.get_dimensions_pixels();
+        let pixel_size = self.get_pixel_size();
         let dimensions_scaled =
-            self.project.image_sequence.get_dimensions_pixels_vec2() * f32::from(self.scale);
-        let (response, painter) = ui.allocate_painter(dimensions_scaled, Sense::click_and_drag());
-        let painter_top_left = response.rect.min;
-        if let Some(pos) = response.interact_pointer_pos() {
-            let Vec2 { x, y } = (pos - painter_top_left) / f32::from(self.scale);
+            self.project.image_sequence.get_dimensions_pixels_vec2() * pixel_size;
+        let ruler_offset = if self.show_rulers {
+            Vec2::splat(Self::RULER_SIZE)
+        } else {
+            Vec2::ZERO
+        };
+        let (response, painter) =
+            ui.allocate_painter(dimensions_scaled + ruler_offset, Sense::click_and_drag());
+        let painter_top_left = response.rect.min + ruler_offset;
+        let canvas_rect = Rect::from_min_size(painter_top_left, dimensions_scaled);
+        self.canvas_rect = canvas_rect;
+        let locked = self
+            .project
+            .image_sequence
+            .is_locked(self.current_frame - 1);
+        let response = if locked {
+            response.on_hover_cursor(CursorIcon::NotAllowed)
+        } else {
+            response
+        };
+        if let Some(pos) = response
+            .interact_pointer_pos()
+            .filter(|&pos| !locked && (response.dragged() || canvas_rect.contains(pos)))
+        {
+            let Vec2 { x, y } = (pos - painter_top_left) / pixel_size;
             let (x, y) = (
                 (x as usize).clamp(0, width_pixels - 1),
                 (y as usize).clamp(0, height_pixels - 1),
             );
-            if response.clicked_by(PointerButton::Primary)
-                || response.dragged_by(PointerButton::Primary)
-            {
-                self.project.image_sequence[[x, y, self.current_frame - 1]] = true;
-            } else if response.clicked_by(PointerButton::Secondary)
-                || response.dragged_by(PointerButton::Secondary)
-            {
-                self.project.image_sequence[[x, y, self.current_frame - 1]] = false;
+            if response.drag_started() {
+                self.push_frame_history(self.current_frame - 1);
+            }
+            let outside_selection = self.draw_within_selection
+                && self
+                    .selection
+                    .is_some_and(|selection| !selection.contains(x, y));
+            if !outside_selection {
+                if response.clicked_by(PointerButton::Primary)
+                    || response.dragged_by(PointerButton::Primary)
+                {
+                    match self.project.image_sequence.get_mode() {
+                        CanvasMode::Monochrome => self.set_pixel(x, y, true),
+                        CanvasMode::Grayscale4Bit => self.project.image_sequence.set_level(
+                            x,
+                            y,
+                            self.current_frame - 1,
+                            self.brush_level,
+                        ),
+                    }
+                } else if response.clicked_by(PointerButton::Secondary)
+                    || response.dragged_by(PointerButton::Secondary)
+                {
+                    self.set_pixel(x, y, false);
+                }
             }
         }
+        let [r, g, b] = self.frame_background(self.current_frame - 1);
         painter.rect_filled(
             Rect::from_min_size(painter_top_left, dimensions_scaled),
             Rounding::none(),
-            Color32::BLACK,
+            Color32::from_rgb(r, g, b),
         );
+        if let Some(reference_texture) = &self.reference_texture {
+            let reference_rects = if self.local_reference {
+                let [width_matrices, height_matrices] =
+                    self.project.image_sequence.get_dimensions_matrices();
+                let cell_size =
+                    pixel_size * f32::from(self.project.image_sequence.get_matrix_size());
+                let uv_cell_size = Vec2::new(
+                    1.0 / f32::from(width_matrices),
+                    1.0 / f32::from(height_matrices),
+                );
+                response
+                    .hover_pos()
+                    .filter(|&pos| canvas_rect.contains(pos))
+                    .map(|pos| {
+                        let cell = ((pos - painter_top_left) / cell_size).floor();
+                        (
+                            Rect::from_min_size(painter_top_left + cell * cell_size, cell_size),
+                            Rect::from_min_size((cell * uv_cell_size).to_pos2(), uv_cell_size),
+                        )
+                    })
+            } else {
+                Some((
+                    canvas_rect,
+                    Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                ))
+            };
+            if let Some((rect, uv)) = reference_rects {
+                painter.image(
+                    reference_texture.id(),
+                    rect,
+                    uv,
+                    Color32::WHITE.linear_multiply(self.reference_opacity),
+                );
+            }
+        }
         let color = Color32::from_rgb(
             self.display_color[0],
             self.display_color[1],
             self.display_color[2],
         );
         if self.onion_skin {
-            if let Some(frame_idx) = self.current_frame.checked_sub(2) {
+            (1..=self.onion_depth).rev().for_each(|depth| {
+                let Some(frame_idx) = (self.current_frame - 1).checked_sub(depth) else {
+                    return;
+                };
+                let [r, g, b] = rotate_hue(
+                    self.display_color,
+                    depth as f32 * Self::ONION_HUE_STEP_DEGREES,
+                );
                 self.render_frame(
                     &painter,
                     painter_top_left,
                     frame_idx,
-                    color.linear_multiply(self.onion_opacity),
+                    Color32::from_rgb(r, g, b).linear_multiply(onion_opacity_at(
+                        self.onion_opacity,
+                        self.onion_falloff,
+                        depth,
+                    )),
                 );
-            }
+            });
         }
         self.render_frame(&painter, painter_top_left, self.current_frame - 1, color);
+        if self.show_heatmap {
+            self.project
+                .image_sequence
+                .pixel_frequency()
+                .into_iter()
+                .enumerate()
+                .for_each(|(i, frequency)| {
+                    let position_scaled = Pos2::new(
+                        (i % width_pixels) as f32 * pixel_size.x,
+                        (i / width_pixels) as f32 * pixel_size.y,
+                    ) + painter_top_left.to_vec2();
+                    painter.rect_filled(
+                        Rect::from_min_size(position_scaled, pixel_size),
+                        Rounding::none(),
+                        Color32::from_rgba_unmultiplied(
+                            (frequency * 255.0) as u8,
+                            0x00,
+                            ((1.0 - frequency) * 255.0) as u8,
+                            0x80,
+                        ),
+                    );
+                });
+        }
         if self.show_grid {
             let [width_matrices, height_matrices] =
                 self.project.image_sequence.get_dimensions_pixels();
             let stroke = Stroke::new(self.stoke_thickness, Color32::WHITE);
             (0..width_matrices).for_each(|x| {
                 painter.vline(
-                    x as f32 * f32::from(self.scale) + painter_top_left.x,
+                    x as f32 * pixel_size.x + painter_top_left.x,
                     painter_top_left.y..=dimensions_scaled.y + painter_top_left.y,
                     stroke,
                 )
@@ -435,178 +1928,2824 @@ impl MainWindow {
             (0..height_matrices).for_each(|y| {
                 painter.hline(
                     painter_top_left.x..=dimensions_scaled.x + painter_top_left.x,
-                    y as f32 * f32::from(self.scale) + painter_top_left.y,
+                    y as f32 * pixel_size.y + painter_top_left.y,
                     stroke,
                 );
             });
         }
+        if let Some(selection) = self.selection {
+            painter.rect_stroke(
+                Rect::from_min_size(
+                    painter_top_left
+                        + Vec2::new(
+                            selection.x as f32 * pixel_size.x,
+                            selection.y as f32 * pixel_size.y,
+                        ),
+                    Vec2::new(
+                        selection.width as f32 * pixel_size.x,
+                        selection.height as f32 * pixel_size.y,
+                    ),
+                ),
+                Rounding::none(),
+                Stroke::new(self.stoke_thickness, Color32::LIGHT_BLUE),
+            );
+        }
+        if self.show_rulers {
+            self.draw_rulers(&painter, painter_top_left, [width_pixels, height_pixels]);
+        }
     }
 
-    fn show_menu(&mut self, ctx: &Context) {
-        TopBottomPanel::top("menu_panel").show(ctx, |ui| {
-            menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("New file").clicked() {
-                        self.new_file_dialog.show = true;
-                        ui.close_menu();
-                    }
-                    if ui
-                        .add(
-                            Button::new("Open file")
-                                .shortcut_text(ctx.format_shortcut(&Self::OPEN_SHORTCUT)),
-                        )
-                        .clicked()
-                    {
-                        self.open_file();
-                        ui.close_menu();
-                    }
-                    if ui
-                        .add(
-                            Button::new("Save file")
-                                .shortcut_text(ctx.format_shortcut(&Self::SAVE_SHORTCUT)),
-                        )
-                        .clicked()
-                    {
-                        self.save_file();
-                        ui.close_menu();
-                    }
-                    if ui.button("Save file as").clicked() {
-                        self.save_file_as();
-                        ui.close_menu();
-                    }
-                    ui.separator();
-                    if ui.button("Import image").clicked() {
-                        self.import_image();
-                        ui.close_menu();
-                    }
-                    if ui.button("Export animation").clicked() {
-                        self.export_animation();
-                        ui.close_menu();
-                    }
-                });
-                ui.menu_button("View", |ui| {
-                    ui.add(
-                        DragValue::new(&mut self.scale)
-                            .clamp_range(1..=64)
-                            .prefix("Scale: ")
-                            .suffix('x'),
-                    );
-                    ui.separator();
-                    ui.checkbox(&mut self.show_grid, "Show grid");
-                    ui.add(
-                        DragValue::new(&mut self.stoke_thickness)
-                            .clamp_range(0.1..=2.0)
-                            .speed(0.1)
-                            .prefix("Stroke: "),
-                    );
-                    ui.checkbox(&mut self.onion_skin, "Onion skin");
-                    ui.add(
-                        DragValue::new(&mut self.onion_opacity)
-                            .clamp_range(0.0..=1.0)
-                            .speed(0.05)
-                            .prefix("Onion skin opacity: "),
+    fn draw_rulers(
+        &self,
+        painter: &Painter,
+        painter_top_left: Pos2,
+        [width_pixels, height_pixels]: [usize; 2],
+    ) {
+        let pixel_size = self.get_pixel_size();
+        let tick_step = if self.scale >= 8 {
+            1
+        } else if self.scale >= 3 {
+            5
+        } else {
+            10
+        };
+        let ruler_top_left = painter_top_left - Vec2::splat(Self::RULER_SIZE);
+        painter.rect_filled(
+            Rect::from_min_size(
+                ruler_top_left,
+                Vec2::new(
+                    width_pixels as f32 * pixel_size.x + Self::RULER_SIZE,
+                    Self::RULER_SIZE,
+                ),
+            ),
+            Rounding::none(),
+            Color32::DARK_GRAY,
+        );
+        painter.rect_filled(
+            Rect::from_min_size(
+                ruler_top_left,
+                Vec2::new(
+                    Self::RULER_SIZE,
+                    height_pixels as f32 * pixel_size.y + Self::RULER_SIZE,
+                ),
+            ),
+            Rounding::none(),
+            Color32::DARK_GRAY,
+        );
+        let matrix_size = usize::from(self.project.image_sequence.get_matrix_size());
+        let stroke = Stroke::new(1.0, Color32::WHITE);
+        (0..=width_pixels)
+            .filter(|x| x % tick_step == 0 || x % matrix_size == 0)
+            .for_each(|x| {
+                let position = x as f32 * pixel_size.x + painter_top_left.x;
+                let tick_length = if x % matrix_size == 0 {
+                    Self::RULER_SIZE
+                } else {
+                    Self::RULER_SIZE / 4.0
+                };
+                painter.vline(
+                    position,
+                    painter_top_left.y - tick_length..=painter_top_left.y,
+                    stroke,
+                );
+                if x % matrix_size == 0 && x != width_pixels {
+                    painter.text(
+                        Pos2::new(position + 2.0, ruler_top_left.y),
+                        Align2::LEFT_TOP,
+                        x.to_string(),
+                        FontId::monospace(8.0),
+                        Color32::WHITE,
                     );
-                });
-                ui.menu_button("Animation", |ui| {
-                    ui.add(
-                        DragValue::new(&mut self.project.frame_rate)
-                            .clamp_range(Self::FPS_RANGE)
-                            .prefix("Frame rate: ")
-                            .suffix(" f/s"),
+                }
+            });
+        (0..=height_pixels)
+            .filter(|y| y % tick_step == 0 || y % matrix_size == 0)
+            .for_each(|y| {
+                let position = y as f32 * pixel_size.y + painter_top_left.y;
+                let tick_length = if y % matrix_size == 0 {
+                    Self::RULER_SIZE
+                } else {
+                    Self::RULER_SIZE / 4.0
+                };
+                painter.hline(
+                    painter_top_left.x - tick_length..=painter_top_left.x,
+                    position,
+                    stroke,
+                );
+                if y % matrix_size == 0 && y != height_pixels {
+                    painter.text(
+                        Pos2::new(ruler_top_left.x, position + 2.0),
+                        Align2::LEFT_TOP,
+                        y.to_string(),
+                        FontId::monospace(8.0),
+                        Color32::WHITE,
                     );
-                    ui.separator();
-                    SlideAnimation::iter().for_each(|slide_animation| {
-                        ui.menu_button(slide_animation.to_string(), |ui| {
-                            Direction::iter().for_each(|direction| {
-                                if ui.button(direction.to_string()).clicked() {
-                                    self.project.image_sequence.slide(
-                                        self.current_frame - 1,
-                                        direction,
-                                        slide_animation,
-                                    );
-                                    ui.close_menu();
+                }
+            });
+    }
+
+    fn show_status_bar(&mut self, ctx: &Context) {
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let total_duration = self
+                .project
+                .image_sequence
+                .total_duration_ms(self.project.frame_rate);
+            ui.label(format!(
+                "Duration: {:.1} s ({} frames @ {} fps)",
+                f64::from(total_duration) / 1000.0,
+                self.project.image_sequence.get_frame_count(),
+                self.project.frame_rate
+            ));
+        });
+    }
+
+    fn show_timeline(&mut self, ctx: &Context) {
+        TopBottomPanel::bottom("timeline_panel").show(ctx, |ui| {
+            ScrollArea::horizontal()
+                .id_source("timeline")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 0.0;
+                        let durations = self
+                            .project
+                            .image_sequence
+                            .iter_frame_durations_ms(self.project.frame_rate)
+                            .collect::<Vec<_>>();
+                        let mut current_frame_rect = None;
+                        durations
+                            .into_iter()
+                            .enumerate()
+                            .for_each(|(idx, duration)| {
+                                let width =
+                                    (duration as f32 * Self::TIMELINE_PIXELS_PER_MS).max(8.0);
+                                let (rect, response) = ui.allocate_exact_size(
+                                    Vec2::new(width, Self::TIMELINE_HEIGHT),
+                                    Sense::click(),
+                                );
+                                if response.clicked() {
+                                    self.current_frame = idx + 1;
+                                }
+                                let is_current_frame = self.current_frame == idx + 1;
+                                if is_current_frame {
+                                    current_frame_rect = Some((rect, duration));
+                                }
+                                ui.painter().rect(
+                                    rect.shrink(1.0),
+                                    Rounding::none(),
+                                    if is_current_frame {
+                                        ui.visuals().selection.bg_fill
+                                    } else {
+                                        ui.visuals().widgets.inactive.bg_fill
+                                    },
+                                    ui.visuals().widgets.inactive.bg_stroke,
+                                );
+                                if self.project.image_sequence.is_keyframe(idx) {
+                                    let center = rect.center_top() + Vec2::new(0.0, 6.0);
+                                    ui.painter().add(Shape::convex_polygon(
+                                        vec![
+                                            center - Vec2::new(0.0, 4.0),
+                                            center + Vec2::new(4.0, 0.0),
+                                            center + Vec2::new(0.0, 4.0),
+                                            center - Vec2::new(4.0, 0.0),
+                                        ],
+                                        Color32::GOLD,
+                                        Stroke::NONE,
+                                    ));
+                                }
+                                ui.painter().text(
+                                    rect.left_center() + Vec2::new(4.0, 0.0),
+                                    Align2::LEFT_CENTER,
+                                    (idx + 1).to_string(),
+                                    FontId::monospace(10.0),
+                                    ui.visuals().text_color(),
+                                );
+
+                                let (_, handle_response) = ui.allocate_exact_size(
+                                    Vec2::new(4.0, Self::TIMELINE_HEIGHT),
+                                    Sense::drag(),
+                                );
+                                let handle_response =
+                                    handle_response.on_hover_cursor(CursorIcon::ResizeHorizontal);
+                                if handle_response.dragged() {
+                                    let new_duration = (duration as f32
+                                        + handle_response.drag_delta().x
+                                            / Self::TIMELINE_PIXELS_PER_MS)
+                                        .round()
+                                        .clamp(1.0, *Self::FRAME_DURATION_RANGE.end() as f32);
+                                    if let Some(frame_duration) =
+                                        self.project.image_sequence.get_frame_duration_mut(idx)
+                                    {
+                                        *frame_duration = new_duration as u32;
+                                    }
                                 }
                             });
+                        if let (true, Some((rect, duration))) = (self.play, current_frame_rect) {
+                            let progress = (self.last_frame_delta.elapsed().as_secs_f32() * 1000.0
+                                / duration as f32)
+                                .min(1.0);
+                            ui.painter().vline(
+                                rect.left() + rect.width() * progress,
+                                rect.top()..=rect.bottom(),
+                                Stroke::new(2.0, Color32::RED),
+                            );
+                        }
+                    });
+                });
+        });
+    }
+
+    fn target_frames(&self) -> Vec<usize> {
+        if self.selected_frames.is_empty() {
+            vec![self.current_frame - 1]
+        } else {
+            self.selected_frames.iter().rev().copied().collect()
+        }
+    }
+
+    fn delete_target_frames(&mut self) {
+        self.target_frames().into_iter().for_each(|idx| {
+            match self.project.image_sequence.delete_frame(idx) {
+                Ok(()) => {}
+                Err(FrameError::LastFrame) => self.project.image_sequence.clear_frame(idx),
+                Err(error) => {
+                    MessageDialog::new()
+                        .set_description(&format!("Could not delete frame, error: {error}"))
+                        .show();
+                }
+            }
+            self.current_frame = self
+                .current_frame
+                .min(self.project.image_sequence.get_frame_count());
+        });
+        self.selected_frames.clear();
+    }
+
+    fn select_all_frames(&mut self) {
+        self.selected_frames = (0..self.project.image_sequence.get_frame_count()).collect();
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, value: bool) {
+        if let Err(error) = self
+            .project
+            .image_sequence
+            .set(x, y, self.current_frame - 1, value)
+        {
+            MessageDialog::new()
+                .set_description(&format!("Could not edit frame, error: {error}"))
+                .show();
+        }
+    }
+
+    fn blit_into_current_frame(&mut self, pixels: &[bool], width: usize) {
+        if let Err(error) =
+            self.project
+                .image_sequence
+                .blit(self.current_frame - 1, pixels, width, 0, 0)
+        {
+            MessageDialog::new()
+                .set_description(&format!("Could not paste into frame, error: {error}"))
+                .show();
+        }
+    }
+
+    fn push_frame_history(&mut self, idx: usize) {
+        self.push_grouped_frame_history(idx, None);
+    }
+
+    fn apply_to_target_frames(&mut self, operation: fn(&mut ImageSequence, usize)) {
+        let group = self.next_history_group;
+        self.next_history_group += 1;
+        self.target_frames().into_iter().for_each(|idx| {
+            self.push_grouped_frame_history(idx, Some(group));
+            operation(&mut self.project.image_sequence, idx);
+        });
+    }
+
+    fn push_grouped_frame_history(&mut self, idx: usize, group: Option<u64>) {
+        let Some(pixels) = self.project.image_sequence.get_active_layer_mut(idx) else {
+            return;
+        };
+        let pixels = pixels.to_vec();
+        let Some(levels) = self.project.image_sequence.get_active_levels_mut(idx) else {
+            return;
+        };
+
+        let history = self.frame_histories.entry(idx).or_default();
+        if history.len() == Self::FRAME_HISTORY_LENGTH {
+            history.pop_front();
+        }
+        history.push_back(FrameHistoryEntry {
+            pixels,
+            levels: levels.to_vec(),
+            group,
+        });
+    }
+
+    fn invert_frame(&mut self, idx: usize) {
+        self.push_frame_history(idx);
+        self.project.image_sequence.invert_frame(idx);
+    }
+
+    fn morph_selection(&mut self, morph: fn(&[bool], usize) -> Vec<bool>) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        self.selection = Selection::from_mask(&morph(&selection.mask(width, height), width), width);
+    }
+
+    fn rotate_canvas(&mut self) {
+        if let Err(DimensionError::NotSquare { width, height }) =
+            self.project.image_sequence.rotate_canvas_cw(false)
+        {
+            if !MessageDialog::new()
+                .set_title("Non-square canvas")
+                .set_description(&format!(
+                    "The canvas is {width}×{height} matrices. Rotating it will resize it to \
+                     {height}×{width}. Continue?"
+                ))
+                .set_buttons(MessageButtons::YesNo)
+                .show()
+            {
+                return;
+            }
+            self.project.image_sequence.rotate_canvas_cw(true).unwrap();
+        }
+        self.frame_histories.clear();
+    }
+
+    fn copy_frame_pixels(&mut self) {
+        let [width, _] = self.project.image_sequence.get_dimensions_pixels();
+        self.pixel_clipboard = Some((
+            self.project
+                .image_sequence
+                .composite_frame(self.current_frame - 1),
+            width,
+        ));
+    }
+
+    fn paste_pixels(&mut self, mirror_horizontally: bool, mirror_vertically: bool) {
+        let Some((mut pixels, width)) = self.pixel_clipboard.clone() else {
+            return;
+        };
+
+        if mirror_horizontally {
+            flip_horizontal_bitmap(&mut pixels, width);
+        }
+        if mirror_vertically {
+            flip_vertical_bitmap(&mut pixels, width);
+        }
+        self.push_frame_history(self.current_frame - 1);
+        if self.paste_mode == PasteMode::Replace {
+            self.project
+                .image_sequence
+                .clear_frame(self.current_frame - 1);
+        }
+        self.blit_into_current_frame(&pixels, width);
+    }
+
+    fn paste_as_new_frame(&mut self) {
+        let Some((pixels, width)) = self.pixel_clipboard.clone() else {
+            return;
+        };
+
+        self.project.image_sequence.insert_frame(self.current_frame);
+        self.current_frame += 1;
+        self.blit_into_current_frame(&pixels, width);
+    }
+
+    fn undo_frame(&mut self) {
+        let idx = self.current_frame - 1;
+        if self.project.image_sequence.is_locked(idx) {
+            return;
+        }
+        let Some(previous) = self
+            .frame_histories
+            .get_mut(&idx)
+            .and_then(VecDeque::pop_back)
+        else {
+            return;
+        };
+
+        if let Some(group) = previous.group {
+            let grouped = self
+                .frame_histories
+                .iter_mut()
+                .filter(|(_, history)| {
+                    history
+                        .back()
+                        .is_some_and(|entry| entry.group == Some(group))
+                })
+                .filter_map(|(&idx, history)| Some((idx, history.pop_back()?)))
+                .collect::<Vec<_>>();
+            grouped
+                .into_iter()
+                .for_each(|(idx, entry)| self.restore_frame_history(idx, &entry));
+        }
+        self.restore_frame_history(idx, &previous);
+    }
+
+    fn restore_frame_history(&mut self, idx: usize, entry: &FrameHistoryEntry) {
+        if let Some(pixels) = self.project.image_sequence.get_active_layer_mut(idx) {
+            pixels.copy_from_slice(&entry.pixels);
+        }
+        if let Some(levels) = self.project.image_sequence.get_active_levels_mut(idx) {
+            levels.copy_from_slice(&entry.levels);
+        }
+    }
+
+    fn update_frame_thumbnails(&mut self, ctx: &Context) {
+        let frame_count = self.project.image_sequence.get_frame_count();
+        self.frame_thumbnails.truncate(frame_count);
+        (0..frame_count).for_each(|idx| {
+            let bitmap = self.project.image_sequence.composite_frame(idx);
+            let levels = self.project.image_sequence.composite_frame_levels(idx);
+            let background = self.frame_background(idx);
+            if let Some(thumbnail) = self.frame_thumbnails.get(idx) {
+                if thumbnail.levels == levels
+                    && thumbnail.color == self.display_color
+                    && thumbnail.background == background
+                {
+                    return;
+                }
+            }
+
+            let image = self.render_frame_image(&levels, background);
+            let color_image = ColorImage::from_rgba_unmultiplied(
+                [
+                    image.width().try_into().unwrap(),
+                    image.height().try_into().unwrap(),
+                ],
+                image.as_raw(),
+            );
+            match self.frame_thumbnails.get_mut(idx) {
+                Some(thumbnail) => {
+                    thumbnail.texture.set(color_image, TextureOptions::NEAREST);
+                    thumbnail.bitmap = bitmap;
+                    thumbnail.levels = levels;
+                    thumbnail.color = self.display_color;
+                    thumbnail.background = background;
+                }
+                None => self.frame_thumbnails.push(FrameThumbnail {
+                    bitmap,
+                    levels,
+                    color: self.display_color,
+                    background,
+                    texture: ctx.load_texture(
+                        format!("frame_thumbnail_{idx}"),
+                        color_image,
+                        TextureOptions::NEAREST,
+                    ),
+                }),
+            }
+        });
+    }
+
+    fn show_frame_grid(&mut self, ui: &mut Ui) {
+        self.update_frame_thumbnails(ui.ctx());
+        let dimensions = self.project.image_sequence.get_dimensions_pixels_vec2();
+        let cell_size = dimensions * (Self::FRAME_GRID_CELL_SIZE / dimensions.max_elem());
+        ScrollArea::vertical()
+            .id_source("frame_grid")
+            .show(ui, |ui| {
+                Grid::new("frame_grid").show(ui, |ui| {
+                    self.frame_thumbnails
+                        .iter()
+                        .enumerate()
+                        .for_each(|(idx, thumbnail)| {
+                            let response = ui
+                                .vertical(|ui| {
+                                    let response = ui.add(
+                                        ImageButton::new(thumbnail.texture.id(), cell_size)
+                                            .selected(self.current_frame == idx + 1),
+                                    );
+                                    ui.label((idx + 1).to_string());
+                                    response
+                                })
+                                .inner;
+                            if response.clicked() {
+                                self.current_frame = idx + 1;
+                            }
+                            if response.double_clicked() {
+                                self.current_frame = idx + 1;
+                                self.frame_grid_view = false;
+                            }
+                            if (idx + 1) % Self::FRAME_GRID_COLUMNS == 0 {
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+    }
+
+    fn frame_time(&self, idx: usize) -> Duration {
+        match self.project.image_sequence.get_frame_duration(idx) {
+            Some(0) | None => Duration::from_nanos(1000000000 / u64::from(self.project.frame_rate)),
+            Some(duration) => Duration::from_millis(duration.into()),
+        }
+    }
+
+    fn show_pip_preview(&mut self, ctx: &Context) {
+        if !self.show_pip {
+            return;
+        }
+
+        let frame_count = self.project.image_sequence.get_frame_count();
+        self.pip_frame %= frame_count;
+        if self.pip_last_delta.elapsed() >= self.frame_time(self.pip_frame) {
+            self.pip_last_delta = Instant::now();
+            self.pip_frame = (self.pip_frame + 1) % frame_count;
+        }
+
+        let Some(thumbnail) = self.frame_thumbnails.get(self.pip_frame) else {
+            return;
+        };
+        let dimensions = self.project.image_sequence.get_dimensions_pixels_vec2();
+        let preview_size = dimensions * (Self::THUMBNAIL_SIZE as f32 / dimensions.max_elem());
+        Area::new("pip_preview")
+            .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-8.0, -8.0))
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.image(thumbnail.texture.id(), preview_size);
+                });
+            });
+    }
+
+    fn show_multi_color_preview(&mut self, ctx: &Context) {
+        let Some(mut colors) = self.multi_color_preview else {
+            return;
+        };
+
+        let mut show = true;
+        Window::new("Multi-colour preview")
+            .open(&mut show)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    colors.iter_mut().for_each(|color| {
+                        ui.color_edit_button_srgb(color);
+                    });
+                });
+                let pixel_size = self.get_pixel_size();
+                let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+                let frame_size =
+                    Vec2::new(width as f32 * pixel_size.x, height as f32 * pixel_size.y);
+                let (response, painter) = ui.allocate_painter(
+                    frame_size * 2.0 + Vec2::splat(Self::MULTI_COLOR_PREVIEW_GAP),
+                    Sense::hover(),
+                );
+                let background = {
+                    let [r, g, b] = self.frame_background(self.current_frame - 1);
+                    Color32::from_rgb(r, g, b)
+                };
+                colors.iter().enumerate().for_each(|(i, &[r, g, b])| {
+                    let top_left = response.rect.min
+                        + Vec2::new(
+                            (i % 2) as f32 * (frame_size.x + Self::MULTI_COLOR_PREVIEW_GAP),
+                            (i / 2) as f32 * (frame_size.y + Self::MULTI_COLOR_PREVIEW_GAP),
+                        );
+                    painter.rect_filled(
+                        Rect::from_min_size(top_left, frame_size),
+                        Rounding::none(),
+                        background,
+                    );
+                    self.render_frame(
+                        &painter,
+                        top_left,
+                        self.current_frame - 1,
+                        Color32::from_rgb(r, g, b),
+                    );
+                });
+            });
+        self.multi_color_preview = show.then_some(colors);
+    }
+
+    fn show_frame_strip(&mut self, ui: &mut Ui) {
+        self.frame_strip_rects.clear();
+        let dimensions = self.project.image_sequence.get_dimensions_pixels_vec2();
+        let thumbnail_size = dimensions * (Self::THUMBNAIL_SIZE as f32 / dimensions.max_elem());
+        ScrollArea::horizontal()
+            .id_source("frame_strip")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    (0..self.project.image_sequence.get_frame_count()).for_each(|idx| {
+                        let selected =
+                            self.current_frame == idx + 1 || self.selected_frames.contains(&idx);
+                        let mut label = RichText::new((idx + 1).to_string());
+                        if self
+                            .find_pattern_dialog
+                            .matches
+                            .iter()
+                            .any(|&(frame_idx, _, _)| frame_idx == idx)
+                        {
+                            label = label.color(Color32::YELLOW);
+                        }
+                        let Some(thumbnail) = self.frame_thumbnails.get(idx) else {
+                            return;
+                        };
+                        let texture_id = thumbnail.texture.id();
+                        let dirty = self.last_saved_bitmaps.get(idx) != Some(&thumbnail.bitmap);
+                        let response = ui.vertical(|ui| {
+                            let thumbnail_response = ui.add(
+                                ImageButton::new(texture_id, thumbnail_size).selected(selected),
+                            );
+                            let label_response = ui.selectable_label(selected, label);
+                            let clicked = thumbnail_response.clicked() || label_response.clicked();
+                            (thumbnail_response, clicked)
+                        });
+                        self.frame_strip_rects.push(response.response.rect);
+                        let (thumbnail_response, clicked) = response.inner;
+                        if self.pulse_thumbnails
+                            && self.play
+                            && self.current_frame == idx + 1
+                            && self.last_frame_delta.elapsed() < Self::THUMBNAIL_PULSE_DURATION
+                        {
+                            let [r, g, b] = self.display_color;
+                            ui.painter().rect_filled(
+                                thumbnail_response.rect,
+                                Rounding::none(),
+                                Color32::from_rgba_unmultiplied(r, g, b, 96),
+                            );
+                        }
+                        if self.project.image_sequence.is_locked(idx) {
+                            ui.painter().text(
+                                thumbnail_response.rect.left_top() + Vec2::new(2.0, 2.0),
+                                Align2::LEFT_TOP,
+                                "🔒",
+                                FontId::proportional(10.0),
+                                Color32::WHITE,
+                            );
+                        }
+                        if dirty {
+                            ui.painter().circle_filled(
+                                thumbnail_response.rect.right_top() + Vec2::new(-4.0, 4.0),
+                                Self::DIRTY_MARKER_RADIUS,
+                                Color32::LIGHT_BLUE,
+                            );
+                        }
+                        thumbnail_response.context_menu(|ui| {
+                            if ui.button("Invert frame").clicked() {
+                                self.invert_frame(idx);
+                                ui.close_menu();
+                            }
+                            if ui.button("Copy as image").clicked() {
+                                self.copy_frame_to_clipboard(idx);
+                                ui.close_menu();
+                            }
+                            if ui.button("Extract to new project").clicked() {
+                                self.extract_frame_as_new_project(idx);
+                                ui.close_menu();
+                            }
                         });
+                        if !clicked {
+                            return;
+                        }
+                        let modifiers = ui.input(|input_state| input_state.modifiers);
+                        if modifiers.shift {
+                            let anchor = self.current_frame - 1;
+                            self.selected_frames
+                                .extend(anchor.min(idx)..=anchor.max(idx));
+                        } else if modifiers.ctrl {
+                            if !self.selected_frames.remove(&idx) {
+                                self.selected_frames.insert(idx);
+                            }
+                        } else {
+                            self.selected_frames.clear();
+                            self.current_frame = idx + 1;
+                        }
+                    });
+                });
+            });
+    }
+
+    fn show_snapshot_dialog(&mut self, ctx: &Context) {
+        let Some(snapshot_name) = &mut self.snapshot_name else {
+            return;
+        };
+
+        let mut show = true;
+        let mut confirmed = false;
+        Window::new("Save snapshot")
+            .open(&mut show)
+            .show(ctx, |ui| {
+                ui.text_edit_singleline(snapshot_name);
+                ui.vertical_centered_justified(|ui| {
+                    confirmed = ui.button("Save").clicked();
+                });
+            });
+
+        if confirmed {
+            let snapshot = (snapshot_name.clone(), self.project.image_sequence.clone());
+            self.project.snapshots.push(snapshot);
+        }
+        if confirmed || !show {
+            self.snapshot_name = None;
+        }
+    }
+
+    fn show_batch_rename_dialog(&mut self, ctx: &Context) {
+        let Some(pattern) = &mut self.batch_rename_pattern else {
+            return;
+        };
+
+        let frame_count = self.project.image_sequence.get_frame_count();
+        let mut show = true;
+        let mut confirmed = false;
+        Window::new("Batch rename frames")
+            .open(&mut show)
+            .show(ctx, |ui| {
+                ui.text_edit_singleline(pattern);
+                ui.label("{n} index, {N} zero-padded index, {total} frame count");
+                ui.separator();
+                ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                    (0..frame_count).for_each(|idx| {
+                        ui.label(format_frame_name(pattern, idx, frame_count));
                     });
                 });
+                ui.vertical_centered_justified(|ui| {
+                    confirmed = ui.button("Rename").clicked();
+                });
+            });
+
+        if confirmed {
+            let pattern = pattern.clone();
+            self.batch_rename_frames(&pattern);
+        }
+        if confirmed || !show {
+            self.batch_rename_pattern = None;
+        }
+    }
+
+    fn batch_rename_frames(&mut self, pattern: &str) {
+        let frame_count = self.project.image_sequence.get_frame_count();
+        (0..frame_count).for_each(|idx| {
+            if let Some(name) = self.project.image_sequence.get_frame_name_mut(idx) {
+                *name = format_frame_name(pattern, idx, frame_count);
+            }
+        });
+    }
+
+    fn show_vu_meter_dialog(&mut self, ctx: &Context) {
+        let dialog = &mut self.vu_meter_dialog;
+        let mut show = dialog.show;
+        let mut generate = false;
+        Window::new("VU meter").open(&mut show).show(ctx, |ui| {
+            ui.label("Levels (0.0 – 1.0, comma separated):");
+            ui.add(TextEdit::multiline(&mut dialog.levels).desired_rows(4));
+            ui.checkbox(&mut dialog.horizontal, "Horizontal bar");
+            ui.vertical_centered_justified(|ui| {
+                generate = ui.button("Generate").clicked();
             });
         });
+
+        if generate {
+            let levels = dialog
+                .levels
+                .split(|character: char| character == ',' || character.is_whitespace())
+                .filter(|level| !level.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<f32>, _>>();
+            match levels {
+                Ok(levels) if !levels.is_empty() => {
+                    self.project
+                        .image_sequence
+                        .vu_meter_animation(&levels, dialog.horizontal);
+                    show = false;
+                }
+                Ok(_) => {
+                    MessageDialog::new()
+                        .set_description("No levels were entered")
+                        .show();
+                }
+                Err(error) => {
+                    MessageDialog::new()
+                        .set_description(&format!("Invalid level: {error}"))
+                        .show();
+                }
+            }
+        }
+        self.vu_meter_dialog.show = show;
+    }
+
+    fn show_export_frames_dialog(&mut self, ctx: &Context) {
+        let dialog = &mut self.export_frames_dialog;
+        let mut show = dialog.show;
+        let mut export = false;
+        Window::new("Export frames")
+            .open(&mut show)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Choose folder…").clicked() {
+                        if let Some(directory) = FileDialog::new().pick_folder() {
+                            dialog.directory = Some(directory);
+                        }
+                    }
+                    ui.label(match &dialog.directory {
+                        Some(directory) => directory.display().to_string(),
+                        None => String::from("No folder selected"),
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    ui.radio_value(&mut dialog.format, ImageFormat::Png, "PNG");
+                    ui.radio_value(&mut dialog.format, ImageFormat::Bmp, "BMP");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Scale:");
+                    ui.add(
+                        DragValue::new(&mut dialog.scale)
+                            .clamp_range(Self::EXPORT_SCALE_RANGE)
+                            .suffix("×"),
+                    );
+                });
+                ui.vertical_centered_justified(|ui| {
+                    export = ui
+                        .add_enabled(dialog.directory.is_some(), Button::new("Export"))
+                        .clicked();
+                });
+            });
+
+        if export {
+            if let Some(directory) = &self.export_frames_dialog.directory {
+                self.export_frames_as_images(
+                    directory,
+                    self.export_frames_dialog.format,
+                    self.export_frames_dialog.scale,
+                );
+                show = false;
+            }
+        }
+        self.export_frames_dialog.show = show;
+    }
+
+    fn show_find_pattern_dialog(&mut self, ctx: &Context) {
+        let dialog = &mut self.find_pattern_dialog;
+        let mut show = dialog.show;
+        Window::new("Find pattern").open(&mut show).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let width_changed = ui
+                    .add(
+                        DragValue::new(&mut dialog.width)
+                            .clamp_range(1..=8)
+                            .prefix("Width: "),
+                    )
+                    .changed();
+                let height_changed = ui
+                    .add(
+                        DragValue::new(&mut dialog.height)
+                            .clamp_range(1..=8)
+                            .prefix("Height: "),
+                    )
+                    .changed();
+                if width_changed || height_changed {
+                    dialog.pattern = vec![false; dialog.width * dialog.height];
+                    dialog.matches.clear();
+                }
+            });
+            let cell_size = Vec2::splat(16.0);
+            let (response, painter) = ui.allocate_painter(
+                Vec2::new(dialog.width as f32, dialog.height as f32) * cell_size,
+                Sense::click(),
+            );
+            if let Some(pos) = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked())
+            {
+                let Vec2 { x, y } = (pos - response.rect.min) / cell_size;
+                let (x, y) = (
+                    (x as usize).min(dialog.width - 1),
+                    (y as usize).min(dialog.height - 1),
+                );
+                dialog.pattern[y * dialog.width + x] ^= true;
+                dialog.matches.clear();
+            }
+            dialog.pattern.iter().enumerate().for_each(|(i, &pixel)| {
+                painter.rect(
+                    Rect::from_min_size(
+                        response.rect.min
+                            + Vec2::new((i % dialog.width) as f32, (i / dialog.width) as f32)
+                                * cell_size,
+                        cell_size,
+                    ),
+                    Rounding::none(),
+                    if pixel {
+                        Color32::WHITE
+                    } else {
+                        Color32::BLACK
+                    },
+                    Stroke::new(1.0, Color32::GRAY),
+                );
+            });
+            if ui.button("Find").clicked() {
+                dialog.matches = self.project.image_sequence.find_pattern(
+                    &dialog.pattern,
+                    dialog.width,
+                    dialog.height,
+                );
+            }
+            ui.label(format!("{} matches", dialog.matches.len()));
+            ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                dialog.matches.iter().for_each(|&(frame_idx, x, y)| {
+                    if ui
+                        .link(format!("Frame {} at ({x}, {y})", frame_idx + 1))
+                        .clicked()
+                    {
+                        self.current_frame = frame_idx + 1;
+                    }
+                });
+            });
+        });
+        self.find_pattern_dialog.show = show;
+        if !show {
+            self.find_pattern_dialog.matches.clear();
+        }
+    }
+
+    fn show_layers(&mut self, ui: &mut Ui) {
+        let image_sequence = &mut self.project.image_sequence;
+        let frame_idx = self.current_frame - 1;
+        let mut deleted_layer = None;
+        Grid::new("layers_grid").striped(true).show(ui, |ui| {
+            (0..image_sequence.get_layer_count()).for_each(|layer| {
+                if ui
+                    .radio(image_sequence.get_active_layer() == layer, "")
+                    .clicked()
+                {
+                    image_sequence.set_active_layer(layer);
+                }
+                let mut enabled = image_sequence.is_layer_enabled(frame_idx, layer);
+                if ui
+                    .checkbox(&mut enabled, "")
+                    .on_hover_text("Visible in the current frame")
+                    .changed()
+                {
+                    image_sequence.toggle_layer(frame_idx, layer);
+                }
+                let mut name = image_sequence.get_layer_name(layer).to_owned();
+                if ui
+                    .add(TextEdit::singleline(&mut name).desired_width(100.0))
+                    .changed()
+                {
+                    image_sequence.rename_layer(layer, &name);
+                }
+                if ui.small_button("⬆").clicked() {
+                    image_sequence.move_layer_up(layer);
+                }
+                if ui.small_button("⬇").clicked() {
+                    image_sequence.move_layer_down(layer);
+                }
+                if ui
+                    .add_enabled(
+                        image_sequence.get_layer_count() > 1,
+                        Button::new("🗑").small(),
+                    )
+                    .clicked()
+                {
+                    deleted_layer = Some(layer);
+                }
+                ui.end_row();
+            });
+        });
+        if let Some(layer) = deleted_layer {
+            image_sequence.delete_layer(layer);
+        }
+        if ui.button("Add layer").clicked() {
+            image_sequence.add_layer();
+        }
+    }
+
+    fn show_statistics(&mut self, ui: &mut Ui) {
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("statistics_grid").striped(true).show(ui, |ui| {
+                ui.label("Frame");
+                ui.label("Lit pixels");
+                ui.label("Fill");
+                ui.label("Notes");
+                ui.end_row();
+                let frames = self
+                    .project
+                    .image_sequence
+                    .iter_frames()
+                    .collect::<Vec<_>>();
+                (0..self.project.image_sequence.get_frame_count()).for_each(|idx| {
+                    let stats = self.project.image_sequence.frame_stats(idx);
+                    let duplicate_of = (0..idx).find(|&other| frames[other] == frames[idx]);
+                    if ui
+                        .selectable_label(self.current_frame == idx + 1, (idx + 1).to_string())
+                        .clicked()
+                    {
+                        self.current_frame = idx + 1;
+                    }
+                    ui.label(format!("{}/{}", stats.lit, stats.total));
+                    ui.label(format!("{:.1} %", stats.fill_percentage()));
+                    ui.label(match (stats.lit, duplicate_of) {
+                        (0, _) => String::from("Empty"),
+                        (_, Some(other)) => format!("Duplicate of {}", other + 1),
+                        _ => String::new(),
+                    });
+                    ui.end_row();
+                });
+            });
+        });
+    }
+
+    fn show_menu(&mut self, ctx: &Context) {
+        TopBottomPanel::top("menu_panel").show(ctx, |ui| {
+            menu::bar(ui, |ui| {
+                ui.menu_button("File", |ui| {
+                    if ui.button("New file").clicked() {
+                        self.new_file_dialog.show = true;
+                        self.new_file_dialog.width = self.default_project_settings.width;
+                        self.new_file_dialog.height = self.default_project_settings.height;
+                        self.new_file_dialog.matrix_size =
+                            self.default_project_settings.matrix_size;
+                        self.new_file_dialog.frame_rate = self.default_project_settings.frame_rate;
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add(
+                            Button::new("Open file")
+                                .shortcut_text(ctx.format_shortcut(&Self::OPEN_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.open_file();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add(
+                            Button::new("Save file")
+                                .shortcut_text(ctx.format_shortcut(&Self::SAVE_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.save_file();
+                        ui.close_menu();
+                    }
+                    if ui.button("Save file as").clicked() {
+                        self.save_file_as();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(self.current_file.is_some(), Button::new("Revert to saved"))
+                        .clicked()
+                    {
+                        self.revert();
+                        ui.close_menu();
+                    }
+                    ui.horizontal(|ui| {
+                        let frame_count = self.project.image_sequence.get_frame_count();
+                        let start = self.export_range[0].clamp(1, frame_count);
+                        let end = self.export_range[1].clamp(start, frame_count);
+                        self.export_range = [start, end];
+                        if ui.button("Export frame range").clicked() {
+                            if let Some(path) = FileDialog::new()
+                                .add_filter("BSON file", &["bson"])
+                                .add_filter("Compressed BSON file", &["bsonz"])
+                                .add_filter("CBOR file", &["cbor"])
+                                .save_file()
+                            {
+                                self.export_subrange(start - 1, end - 1, &path);
+                            }
+                            ui.close_menu();
+                        }
+                        ui.add(
+                            DragValue::new(&mut self.export_range[0]).clamp_range(1..=frame_count),
+                        );
+                        ui.label("–");
+                        ui.add(
+                            DragValue::new(&mut self.export_range[1])
+                                .clamp_range(start..=frame_count),
+                        );
+                    });
+                    ui.separator();
+                    if ui.button("Import from clipboard").clicked() {
+                        self.import_from_clipboard();
+                        ui.close_menu();
+                    }
+                    if ui.button("Import XBM").clicked() {
+                        self.import_xbm();
+                        ui.close_menu();
+                    }
+                    if ui.button("Import YAML…").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("YAML file", &["yaml", "yml"])
+                            .pick_file()
+                        {
+                            self.open_yaml(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Import image").clicked() {
+                        self.import_image();
+                        ui.close_menu();
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Import emoji").clicked() {
+                            if let Some(codepoint) = self.emoji_text.chars().next() {
+                                self.import_emoji(codepoint);
+                            }
+                            ui.close_menu();
+                        }
+                        ui.add(TextEdit::singleline(&mut self.emoji_text).desired_width(32.0));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Scaling:");
+                        ImportMode::iter().for_each(|import_mode| {
+                            ui.radio_value(
+                                &mut self.import_mode,
+                                import_mode,
+                                import_mode.to_string(),
+                            );
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Import video").clicked() {
+                            self.import_from_video();
+                            ui.close_menu();
+                        }
+                        ui.add(
+                            DragValue::new(&mut self.video_frame_count)
+                                .clamp_range(Self::VIDEO_FRAME_COUNT_RANGE)
+                                .suffix(" frames"),
+                        );
+                    });
+                    if ui.button("Batch convert folder").clicked() {
+                        self.batch_convert_folder();
+                        ui.close_menu();
+                    }
+                    if ui.button("Export animation").clicked() {
+                        self.export_animation();
+                        ui.close_menu();
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Export optimized GIF").clicked() {
+                            if let Some(path) = FileDialog::new()
+                                .add_filter("GIF file", &["gif"])
+                                .save_file()
+                            {
+                                self.export_gif_looped(&path, self.gif_loop_count);
+                            }
+                            ui.close_menu();
+                        }
+                        ui.add(DragValue::new(&mut self.gif_loop_count).prefix("Loops: "))
+                            .on_hover_text("0 loops forever");
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Export CSS animation").clicked() {
+                            if let Some(path) = FileDialog::new()
+                                .add_filter("CSS file", &["css"])
+                                .save_file()
+                            {
+                                self.export_css(&path, &self.css_selector);
+                            }
+                            ui.close_menu();
+                        }
+                        ui.add(
+                            TextEdit::singleline(&mut self.css_selector)
+                                .hint_text("Selector")
+                                .desired_width(100.0),
+                        );
+                    });
+                    if ui.button("Export animated SVG").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("SVG file", &["svg"])
+                            .save_file()
+                        {
+                            self.export_svg(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export Protobuf").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("Protobuf binary", &["pb"])
+                            .save_file()
+                        {
+                            self.export_protobuf(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export timing JSON…").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("JSON file", &["json"])
+                            .save_file()
+                        {
+                            self.export_timing_json(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export MessagePack…").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("MessagePack file", &["msgpack"])
+                            .save_file()
+                        {
+                            self.export_msgpack(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export YAML…").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("YAML file", &["yaml", "yml"])
+                            .save_file()
+                        {
+                            self.save_yaml(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export XBM").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("XBM image", &["xbm"])
+                            .save_file()
+                        {
+                            self.export_xbm(&path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export BMP").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("BMP image", &["bmp"])
+                            .save_file()
+                        {
+                            self.export_bmp(self.current_frame - 1, &path);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Export frames…").clicked() {
+                        self.export_frames_dialog.show = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Export HTML viewer").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("HTML file", &["html"])
+                            .save_file()
+                        {
+                            self.export_html_viewer(&path);
+                        }
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Edit", |ui| {
+                    if ui
+                        .add(
+                            Button::new("Select all frames")
+                                .shortcut_text(ctx.format_shortcut(&Self::SELECT_ALL_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.select_all_frames();
+                        ui.close_menu();
+                    }
+                    if ui.button("Deselect all frames").clicked() {
+                        self.selected_frames.clear();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui
+                        .add(
+                            Button::new("Copy frame")
+                                .shortcut_text(ctx.format_shortcut(&Self::COPY_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.copy_frame_pixels();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add(
+                            Button::new("Copy frame as image")
+                                .shortcut_text(ctx.format_shortcut(&Self::COPY_IMAGE_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.copy_frame_to_clipboard(self.current_frame - 1);
+                        ui.close_menu();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Paste mode:");
+                        PasteMode::iter().for_each(|paste_mode| {
+                            ui.radio_value(
+                                &mut self.paste_mode,
+                                paste_mode,
+                                paste_mode.to_string(),
+                            );
+                        });
+                    });
+                    ui.add_enabled_ui(self.pixel_clipboard.is_some(), |ui| {
+                        if ui
+                            .add(
+                                Button::new("Paste")
+                                    .shortcut_text(ctx.format_shortcut(&Self::PASTE_SHORTCUT)),
+                            )
+                            .clicked()
+                        {
+                            self.paste_pixels(false, false);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add(Button::new("Paste mirrored horizontally").shortcut_text(
+                                ctx.format_shortcut(&Self::PASTE_MIRRORED_HORIZONTALLY_SHORTCUT),
+                            ))
+                            .clicked()
+                        {
+                            self.paste_pixels(true, false);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add(Button::new("Paste mirrored vertically").shortcut_text(
+                                ctx.format_shortcut(&Self::PASTE_MIRRORED_VERTICALLY_SHORTCUT),
+                            ))
+                            .clicked()
+                        {
+                            self.paste_pixels(false, true);
+                            ui.close_menu();
+                        }
+                        if ui.button("Paste as new frame").clicked() {
+                            self.paste_as_new_frame();
+                            ui.close_menu();
+                        }
+                    });
+                    ui.separator();
+                    if ui.button("Invert frames").clicked() {
+                        self.apply_to_target_frames(ImageSequence::invert_frame);
+                        ui.close_menu();
+                    }
+                    if ui.button("Flip frames horizontally").clicked() {
+                        self.apply_to_target_frames(ImageSequence::flip_horizontal);
+                        ui.close_menu();
+                    }
+                    if ui.button("Flip frames vertically").clicked() {
+                        self.apply_to_target_frames(ImageSequence::flip_vertical);
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete frames").clicked() {
+                        self.delete_target_frames();
+                        ui.close_menu();
+                    }
+                    if ui.button("Extract frame to new project").clicked() {
+                        self.extract_frame_as_new_project(self.current_frame - 1);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Find pattern…").clicked() {
+                        self.find_pattern_dialog.show = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Batch rename frames…").clicked() {
+                        self.batch_rename_pattern = Some(String::from("Frame {n}"));
+                        ui.close_menu();
+                    }
+                    if ui.button("Similarity matrix…").clicked() {
+                        self.show_similarity_matrix = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Save snapshot…").clicked() {
+                        self.snapshot_name =
+                            Some(format!("Snapshot {}", self.project.snapshots.len() + 1));
+                        ui.close_menu();
+                    }
+                    ui.add_enabled_ui(!self.project.snapshots.is_empty(), |ui| {
+                        ui.menu_button("Restore snapshot", |ui| {
+                            let mut restored = None;
+                            self.project
+                                .snapshots
+                                .iter()
+                                .enumerate()
+                                .for_each(|(i, (name, _))| {
+                                    if ui.button(name).clicked() {
+                                        restored = Some(i);
+                                    }
+                                });
+                            if let Some(i) = restored {
+                                self.project.image_sequence = self.project.snapshots[i].1.clone();
+                                self.current_frame = self
+                                    .current_frame
+                                    .min(self.project.image_sequence.get_frame_count());
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                });
+                ui.menu_button("View", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            DragValue::new(&mut self.scale)
+                                .clamp_range(1..=64)
+                                .prefix("Scale: ")
+                                .suffix('x'),
+                        );
+                        if ui
+                            .add(
+                                Button::new("Reset")
+                                    .shortcut_text(ctx.format_shortcut(&Self::RESET_VIEW_SHORTCUT)),
+                            )
+                            .clicked()
+                        {
+                            self.scale = 1;
+                        }
+                    });
+                    ui.add(
+                        DragValue::new(&mut self.pixel_aspect)
+                            .clamp_range(0.25..=4.0)
+                            .speed(0.05)
+                            .prefix("Pixel aspect: "),
+                    );
+                    ui.separator();
+                    ui.checkbox(&mut self.show_grid, "Show grid");
+                    ui.checkbox(&mut self.show_heatmap, "Heatmap");
+                    ui.checkbox(&mut self.show_pip, "PiP preview");
+                    ui.checkbox(&mut self.pulse_thumbnails, "Pulse thumbnails while playing");
+                    ui.checkbox(&mut self.frame_grid_view, "Show all frames grid");
+                    let mut multi_color_preview = self.multi_color_preview.is_some();
+                    if ui
+                        .checkbox(&mut multi_color_preview, "Multi-colour preview")
+                        .changed()
+                    {
+                        self.multi_color_preview =
+                            multi_color_preview.then_some(Self::MULTI_COLOR_PREVIEW_COLORS);
+                    }
+                    ui.checkbox(&mut self.show_rulers, "Show rulers");
+                    ui.add(
+                        DragValue::new(&mut self.stoke_thickness)
+                            .clamp_range(0.1..=2.0)
+                            .speed(0.1)
+                            .prefix("Stroke: "),
+                    );
+                    ui.checkbox(&mut self.onion_skin, "Onion skin");
+                    ui.add(
+                        DragValue::new(&mut self.onion_opacity)
+                            .clamp_range(0.0..=1.0)
+                            .speed(0.05)
+                            .prefix("Onion skin opacity: "),
+                    );
+                    ui.add(
+                        DragValue::new(&mut self.onion_falloff)
+                            .clamp_range(0.0..=1.0)
+                            .speed(0.05)
+                            .prefix("Onion skin falloff: "),
+                    )
+                    .on_hover_text("How much more transparent each older ghost frame is");
+                    ui.add(
+                        DragValue::new(&mut self.onion_depth)
+                            .clamp_range(Self::ONION_DEPTH_RANGE)
+                            .prefix("Onion skin depth: "),
+                    );
+                    ui.separator();
+                    if ui.button("Load reference image…").clicked() {
+                        self.load_reference_image(ctx);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.reference_image.is_some(),
+                            Button::new("Clear reference image"),
+                        )
+                        .clicked()
+                    {
+                        self.reference_image = None;
+                        self.reference_texture = None;
+                        ui.close_menu();
+                    }
+                    ui.add(
+                        DragValue::new(&mut self.reference_opacity)
+                            .clamp_range(0.0..=1.0)
+                            .speed(0.05)
+                            .prefix("Reference opacity: "),
+                    );
+                    ui.checkbox(&mut self.local_reference, "Local reference")
+                        .on_hover_text("Only show the reference inside the hovered 8×8 block");
+                });
+                ui.menu_button("Animation", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            DragValue::new(&mut self.project.frame_rate)
+                                .clamp_range(Self::FPS_RANGE)
+                                .prefix("Frame rate: ")
+                                .suffix(" f/s"),
+                        );
+                        if let Some(limit) = self
+                            .project
+                            .hardware_fps_limit
+                            .filter(|&limit| self.project.frame_rate > limit)
+                        {
+                            ui.colored_label(Color32::YELLOW, "⚠")
+                                .on_hover_text(format!(
+                                    "Exceeds the hardware limit of {limit} f/s"
+                                ));
+                        }
+                    });
+                    if ui.button("Hardware profile…").clicked() {
+                        self.show_hardware_profile = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.menu_button("Edit frame", |ui| {
+                        if ui
+                            .add(
+                                Button::new("Invert frame").shortcut_text(
+                                    ctx.format_shortcut(&Self::INVERT_FRAME_SHORTCUT),
+                                ),
+                            )
+                            .clicked()
+                        {
+                            self.invert_frame(self.current_frame - 1);
+                            ui.close_menu();
+                        }
+                    });
+                    if ui
+                        .add_enabled(
+                            self.selection.is_some(),
+                            Button::new("Grow selection")
+                                .shortcut_text(ctx.format_shortcut(&Self::GROW_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.morph_selection(dilate_bitmap);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.selection.is_some(),
+                            Button::new("Shrink selection")
+                                .shortcut_text(ctx.format_shortcut(&Self::SHRINK_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.morph_selection(erode_bitmap);
+                        ui.close_menu();
+                    }
+                    if ui.button("Rotate canvas 90°").clicked() {
+                        self.rotate_canvas();
+                        ui.close_menu();
+                    }
+                    let leading_blank = self.project.image_sequence.count_leading_blank();
+                    let trailing_blank = self.project.image_sequence.count_trailing_blank();
+                    if ui
+                        .button("Trim blank frames")
+                        .on_hover_text(format!(
+                            "{leading_blank} leading and {trailing_blank} trailing blank frames"
+                        ))
+                        .clicked()
+                    {
+                        self.project.image_sequence.trim_blank_frames();
+                        self.current_frame = self
+                            .current_frame
+                            .saturating_sub(leading_blank)
+                            .clamp(1, self.project.image_sequence.get_frame_count());
+                        ui.close_menu();
+                    }
+                    ui.menu_button("Tweens", |ui| {
+                        ui.add(
+                            DragValue::new(&mut self.tween_method.steps)
+                                .clamp_range(1..=32)
+                                .prefix("Steps: "),
+                        );
+                        DitherMethod::iter().for_each(|dither_method| {
+                            ui.radio_value(
+                                &mut self.tween_method.dither,
+                                dither_method,
+                                dither_method.to_string(),
+                            );
+                        });
+                        if ui
+                            .button("Generate tweens")
+                            .on_hover_text("Replaces all frames between consecutive keyframes")
+                            .clicked()
+                        {
+                            self.project
+                                .image_sequence
+                                .generate_tweens(self.tween_method);
+                            self.current_frame = self
+                                .current_frame
+                                .min(self.project.image_sequence.get_frame_count());
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button("Text", |ui| {
+                        if ui.button("Load BDF font…").clicked() {
+                            self.load_pixel_font();
+                            ui.close_menu();
+                        }
+                        ui.text_edit_singleline(&mut self.text_tool_text);
+                        if ui
+                            .button("Draw text")
+                            .on_hover_text(
+                                "Falls back to the built-in 4×8 font when the text does not fit",
+                            )
+                            .clicked()
+                        {
+                            let [canvas_width, _] =
+                                self.project.image_sequence.get_dimensions_pixels();
+                            let (pixels, width) = self
+                                .pixel_font
+                                .as_ref()
+                                .map(|pixel_font| pixel_font.render(&self.text_tool_text))
+                                .filter(|&(_, width)| width <= canvas_width)
+                                .unwrap_or_else(|| {
+                                    PixelFont::compact_4x8().render(&self.text_tool_text)
+                                });
+                            if width != 0 {
+                                self.blit_into_current_frame(&pixels, width);
+                            }
+                            ui.close_menu();
+                        }
+                    });
+                    if ui.button("VU meter…").clicked() {
+                        self.vu_meter_dialog.show = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    SlideAnimation::iter().for_each(|slide_animation| {
+                        ui.menu_button(slide_animation.to_string(), |ui| {
+                            Direction::iter().for_each(|direction| {
+                                if ui.button(direction.to_string()).clicked() {
+                                    self.project.image_sequence.slide(
+                                        self.current_frame - 1,
+                                        direction,
+                                        slide_animation,
+                                    );
+                                    ui.close_menu();
+                                }
+                            });
+                        });
+                    });
+                    ui.menu_button("Smooth slide", |ui| {
+                        ui.add(
+                            DragValue::new(&mut self.smooth_slide_pixels)
+                                .clamp_range(1..=128)
+                                .prefix("Distance: ")
+                                .suffix(" px"),
+                        );
+                        ui.add(
+                            DragValue::new(&mut self.smooth_slide_steps)
+                                .clamp_range(1..=128)
+                                .prefix("Steps: "),
+                        );
+                        Direction::iter().for_each(|direction| {
+                            if ui.button(direction.to_string()).clicked() {
+                                self.project.image_sequence.slide_smooth(
+                                    self.current_frame - 1,
+                                    direction,
+                                    self.smooth_slide_pixels,
+                                    self.smooth_slide_steps,
+                                );
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    ui.menu_button("Pixel sort", |ui| {
+                        ui.add(
+                            DragValue::new(&mut self.pixel_sort_steps)
+                                .clamp_range(1..=64)
+                                .prefix("Steps: "),
+                        );
+                        Axis::iter().for_each(|axis| {
+                            if ui.button(axis.to_string()).clicked() {
+                                self.project.image_sequence.pixel_sort_animation(
+                                    self.current_frame - 1,
+                                    axis,
+                                    self.pixel_sort_steps,
+                                );
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    ui.menu_button("Test patterns", |ui| {
+                        if ui.button("All on").clicked() {
+                            self.project
+                                .image_sequence
+                                .test_pattern_all_on(self.current_frame - 1);
+                            ui.close_menu();
+                        }
+                        if ui.button("Border").clicked() {
+                            self.project
+                                .image_sequence
+                                .test_pattern_border(self.current_frame - 1);
+                            ui.close_menu();
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("Grid").clicked() {
+                                self.project.image_sequence.test_pattern_grid(
+                                    self.current_frame - 1,
+                                    self.grid_pattern_step,
+                                );
+                                ui.close_menu();
+                            }
+                            ui.add(
+                                DragValue::new(&mut self.grid_pattern_step)
+                                    .clamp_range(1..=64)
+                                    .prefix("Step: "),
+                            );
+                        });
+                    });
+                    ui.menu_button("Shake", |ui| {
+                        ui.add(
+                            DragValue::new(&mut self.shake_frames)
+                                .clamp_range(1..=64)
+                                .prefix("Frames: "),
+                        );
+                        ui.add(
+                            DragValue::new(&mut self.shake_amplitude)
+                                .clamp_range(1..=8)
+                                .prefix("Amplitude: ")
+                                .suffix(" px"),
+                        );
+                        if ui.button("Generate").clicked() {
+                            self.project.image_sequence.shake_animation(
+                                self.current_frame - 1,
+                                self.shake_frames,
+                                self.shake_amplitude,
+                            );
+                            ui.close_menu();
+                        }
+                    });
+                });
+                ui.menu_button("Help", |ui| {
+                    if ui.button("Keyboard shortcuts").clicked() {
+                        self.show_shortcuts = true;
+                        ui.close_menu();
+                    }
+                });
+            });
+        });
+    }
+
+    fn show_shortcuts_dialog(&mut self, ctx: &Context) {
+        Window::new("Keyboard shortcuts")
+            .open(&mut self.show_shortcuts)
+            .resizable(false)
+            .show(ctx, |ui| {
+                Grid::new("shortcuts_grid").striped(true).show(ui, |ui| {
+                    [
+                        (Self::OPEN_SHORTCUT, "Open"),
+                        (Self::SAVE_SHORTCUT, "Save"),
+                        (Self::SELECT_ALL_SHORTCUT, "Select all frames"),
+                        (Self::INVERT_FRAME_SHORTCUT, "Invert frame"),
+                        (Self::GROW_SHORTCUT, "Grow selection"),
+                        (Self::SHRINK_SHORTCUT, "Shrink selection"),
+                        (Self::FRAME_UNDO_SHORTCUT, "Undo in current frame"),
+                        (Self::COPY_SHORTCUT, "Copy frame"),
+                        (Self::COPY_IMAGE_SHORTCUT, "Copy frame as image"),
+                        (Self::PASTE_SHORTCUT, "Paste"),
+                        (
+                            Self::PASTE_MIRRORED_HORIZONTALLY_SHORTCUT,
+                            "Paste mirrored horizontally",
+                        ),
+                        (
+                            Self::PASTE_MIRRORED_VERTICALLY_SHORTCUT,
+                            "Paste mirrored vertically",
+                        ),
+                        (Self::RESET_VIEW_SHORTCUT, "Reset zoom"),
+                    ]
+                    .iter()
+                    .for_each(|(shortcut, description)| {
+                        ui.monospace(ctx.format_shortcut(shortcut));
+                        ui.label(*description);
+                        ui.end_row();
+                    });
+                });
+            });
+    }
+
+    fn show_similarity_matrix_dialog(&mut self, ctx: &Context) {
+        let image_sequence = &self.project.image_sequence;
+        let frame_count = image_sequence.get_frame_count();
+        let [width, height] = image_sequence.get_dimensions_pixels();
+        let pixel_count = (width * height) as f32;
+        Window::new("Similarity matrix")
+            .open(&mut self.show_similarity_matrix)
+            .show(ctx, |ui| {
+                ScrollArea::both().show(ui, |ui| {
+                    Grid::new("similarity_grid").show(ui, |ui| {
+                        ui.label("");
+                        (1..=frame_count).for_each(|column| {
+                            ui.strong(column.to_string());
+                        });
+                        ui.end_row();
+                        (0..frame_count).for_each(|a| {
+                            ui.strong((a + 1).to_string());
+                            (0..frame_count).for_each(|b| {
+                                let distance = image_sequence.hamming_distance(a, b);
+                                let t = distance as f32 / pixel_count;
+                                let color = Color32::from_rgb(
+                                    (t * 255.0) as u8,
+                                    ((1.0 - t) * 255.0) as u8,
+                                    0,
+                                );
+                                ui.label(
+                                    RichText::new(distance.to_string())
+                                        .monospace()
+                                        .color(Color32::BLACK)
+                                        .background_color(color),
+                                );
+                            });
+                            ui.end_row();
+                        });
+                    });
+                });
+            });
+    }
+
+    fn show_hardware_profile_dialog(&mut self, ctx: &Context) {
+        Window::new("Hardware profile")
+            .open(&mut self.show_hardware_profile)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.radio_value(&mut self.project.hardware_fps_limit, None, "No limit");
+                HardwareProfile::iter().for_each(|profile| {
+                    ui.radio_value(
+                        &mut self.project.hardware_fps_limit,
+                        Some(profile.fps_limit()),
+                        format!("{profile} ({} Hz)", profile.fps_limit()),
+                    );
+                });
+                if let Some(limit) = &mut self.project.hardware_fps_limit {
+                    ui.add(
+                        DragValue::new(limit)
+                            .clamp_range(1..=1000)
+                            .prefix("Limit: ")
+                            .suffix(" f/s"),
+                    );
+                }
+            });
+    }
+
+    fn import_xbm(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("XBM image", &["xbm"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let Some(image_sequence) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| ImageSequence::from_xbm(&contents))
+        else {
+            MessageDialog::new()
+                .set_description(&format!("Could not parse XBM file {}", path.display()))
+                .show();
+            return;
+        };
+
+        self.replace_project(Project::new(image_sequence, self.project.frame_rate), None);
+    }
+
+    fn import_image(&mut self) {
+        let Some(path) = FileDialog::new()
+            .pick_file() else {
+            return;
+        };
+
+        self.import_image_from_path(&path);
+    }
+
+    fn import_image_from_path(&mut self, path: &Path) {
+        let Some(gray_image) = self.read_dithered_image(path) else {
+            return;
+        };
+
+        self.project
+            .image_sequence
+            .insert_frame(self.current_frame - 1);
+        self.write_image_to_frame(&gray_image, self.current_frame - 1);
+    }
+
+    fn import_emoji(&mut self, codepoint: char) {
+        let Some(font) = SYMBOL_FONT_PATHS
+            .iter()
+            .filter_map(|path| fs::read(path).ok())
+            .filter_map(|bytes| Font::from_bytes(bytes, FontSettings::default()).ok())
+            .find(|font| font.lookup_glyph_index(codepoint) != 0)
+        else {
+            MessageDialog::new()
+                .set_description(&format!("No installed symbol font contains {codepoint}"))
+                .show();
+            return;
+        };
+
+        let (metrics, coverage) = font.rasterize(codepoint, EMOJI_RASTER_SIZE);
+        let Some(image) = GrayImage::from_raw(
+            u32::try_from(metrics.width).unwrap(),
+            u32::try_from(metrics.height).unwrap(),
+            coverage,
+        )
+        .filter(|image| image.width() != 0 && image.height() != 0) else {
+            MessageDialog::new()
+                .set_description(&format!("{codepoint} has no visible outline"))
+                .show();
+            return;
+        };
+
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let gray_image = match dither_image(
+            DynamicImage::ImageLuma8(image),
+            width,
+            height,
+            self.import_mode,
+        ) {
+            Ok(gray_image) => gray_image,
+            Err(error) => {
+                MessageDialog::new().set_description(&error).show();
+                return;
+            }
+        };
+
+        self.project
+            .image_sequence
+            .insert_frame(self.current_frame - 1);
+        self.write_image_to_frame(&gray_image, self.current_frame - 1);
+    }
+
+    fn replace_frame_with_image(&mut self, path: &Path, frame_idx: usize) {
+        let Some(gray_image) = self.read_dithered_image(path) else {
+            return;
+        };
+
+        self.write_image_to_frame(&gray_image, frame_idx);
+    }
+
+    fn read_dithered_image(&self, path: &Path) -> Option<GrayImage> {
+        let Some(image) = read_image(path) else {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not read/decode {}",
+                    path.display()
+                ))
+                .show();
+            return None;
+        };
+
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        match dither_image(image, width, height, self.import_mode) {
+            Ok(gray_image) => Some(gray_image),
+            Err(error) => {
+                MessageDialog::new().set_description(&error).show();
+                None
+            }
+        }
+    }
+
+    fn write_image_to_frame(&mut self, gray_image: &GrayImage, frame_idx: usize) {
+        let Some(pixels) = self.project.image_sequence.iter_pixels_mut(frame_idx) else {
+            return;
+        };
+
+        gray_image.iter().zip(pixels).for_each(|(&color, pixel)| {
+            *pixel = color != 0;
+        });
+    }
+
+    fn handle_dropped_files(&mut self, ctx: &Context) {
+        let dropped_files = ctx.input(|input_state| input_state.raw.dropped_files.clone());
+        if dropped_files.is_empty() {
+            return;
+        }
+
+        let pointer_pos = ctx.input(|input_state| input_state.pointer.hover_pos());
+        dropped_files
+            .into_iter()
+            .filter_map(|dropped_file| dropped_file.path)
+            .for_each(|path| {
+                if matches!(
+                    path.extension().and_then(|extension| extension.to_str()),
+                    Some("bson" | "bsonz" | "cbor")
+                ) {
+                    self.open_path(path);
+                    return;
+                }
+
+                let Some(pos) = pointer_pos else {
+                    return;
+                };
+                if let Some(frame_idx) = self
+                    .frame_strip_rects
+                    .iter()
+                    .position(|rect| rect.contains(pos))
+                {
+                    self.replace_frame_with_image(&path, frame_idx);
+                } else if self.canvas_rect.contains(pos) {
+                    self.import_image_from_path(&path);
+                }
+            });
+    }
+
+    fn import_from_video(&mut self) {
+        let Some(path) = FileDialog::new().pick_file() else {
+            return;
+        };
+
+        let Ok(output) = Command::new("ffmpeg")
+            .arg("-i")
+            .arg(&path)
+            .args(["-vframes", &self.video_frame_count.to_string()])
+            .args(["-f", "image2pipe", "-vcodec", "png", "-"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        else {
+            MessageDialog::new()
+                .set_description("Could not run ffmpeg, make sure it is installed and in PATH")
+                .show();
+            return;
+        };
+
+        let images = split_png_stream(&output.stdout)
+            .into_iter()
+            .filter_map(|png| image::load_from_memory_with_format(png, ImageFormat::Png).ok())
+            .collect::<Vec<_>>();
+        if !output.status.success() || images.is_empty() {
+            MessageDialog::new()
+                .set_description(&format!("ffmpeg could not decode {}", path.display()))
+                .show();
+            return;
+        }
+
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let gray_images = match images
+            .into_iter()
+            .map(|image| dither_image(image, width, height, self.import_mode))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(gray_images) => gray_images,
+            Err(error) => {
+                MessageDialog::new().set_description(&error).show();
+                return;
+            }
+        };
+        gray_images
+            .into_iter()
+            .enumerate()
+            .for_each(|(i, gray_image)| {
+                let idx = self.current_frame - 1 + i;
+                self.project.image_sequence.insert_frame(idx);
+                gray_image
+                    .iter()
+                    .zip(self.project.image_sequence.iter_pixels_mut(idx).unwrap())
+                    .for_each(|(&color, pixel)| {
+                        *pixel = color != 0;
+                    });
+            });
+    }
+
+    fn load_pixel_font(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("BDF font", &["bdf"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        match PixelFont::from_bdf(&path) {
+            Ok(pixel_font) => self.pixel_font = Some(pixel_font),
+            Err(error) => {
+                MessageDialog::new()
+                    .set_description(&format!(
+                        "Could not load font {}, error: {error}",
+                        path.display()
+                    ))
+                    .show();
+            }
+        }
+    }
+
+    fn load_reference_image(&mut self, ctx: &Context) {
+        let Some(path) = FileDialog::new().pick_file() else {
+            return;
+        };
+
+        let Some(image) = read_image(&path) else {
+            MessageDialog::new()
+                .set_description(&format!("Could not read/decode {}", path.display()))
+                .show();
+            return;
+        };
+
+        let rgba_image = image.to_rgba8();
+        let color_image = ColorImage::from_rgba_unmultiplied(
+            [
+                rgba_image.width().try_into().unwrap(),
+                rgba_image.height().try_into().unwrap(),
+            ],
+            rgba_image.as_raw(),
+        );
+        self.reference_texture =
+            Some(ctx.load_texture("reference_image", color_image, TextureOptions::LINEAR));
+        self.reference_image = Some(image);
+    }
+
+    fn batch_convert_folder(&mut self) {
+        let Some(folder) = FileDialog::new().pick_folder() else {
+            return;
+        };
+
+        let Ok(entries) = fs::read_dir(&folder) else {
+            MessageDialog::new()
+                .set_description(&format!("Could not read folder {}", folder.display()))
+                .show();
+            return;
+        };
+
+        let mut files = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_file() && ImageFormat::from_path(path).is_ok())
+            .collect::<Vec<_>>();
+        files.sort();
+
+        self.batch_conversion = Some(BatchConversion {
+            files,
+            processed: 0,
+            failed: Vec::new(),
+        });
+    }
+
+    fn show_batch_conversion(&mut self, ctx: &Context) {
+        let Some(batch_conversion) = &mut self.batch_conversion else {
+            return;
+        };
+
+        let Some(path) = batch_conversion.files.get(batch_conversion.processed) else {
+            MessageDialog::new()
+                .set_description(&if batch_conversion.failed.is_empty() {
+                    format!("Converted {} files", batch_conversion.files.len())
+                } else {
+                    format!(
+                        "Converted {} of {} files, failed:\n{}",
+                        batch_conversion.files.len() - batch_conversion.failed.len(),
+                        batch_conversion.files.len(),
+                        batch_conversion
+                            .failed
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    )
+                })
+                .show();
+            self.batch_conversion = None;
+            return;
+        };
+        let path = path.clone();
+
+        Window::new("Batch conversion")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Converting {}", path.display()));
+                ui.add(
+                    ProgressBar::new(
+                        batch_conversion.processed as f32 / batch_conversion.files.len() as f32,
+                    )
+                    .text(format!(
+                        "{}/{}",
+                        batch_conversion.processed,
+                        batch_conversion.files.len()
+                    )),
+                );
+            });
+
+        let [width, height] = self.project.image_sequence.get_dimensions_matrices();
+        let converted = Project::from_image(
+            &path,
+            width,
+            height,
+            self.project.frame_rate,
+            self.import_mode,
+        )
+        .and_then(|project| project.to_bytes(ProjectFormat::Bson).ok())
+        .is_some_and(|serialized| fs::write(path.with_extension("bson"), serialized).is_ok());
+        if !converted {
+            batch_conversion.failed.push(path);
+        }
+        batch_conversion.processed += 1;
+        ctx.request_repaint();
+    }
+
+    fn export_gif_looped(&self, path: &Path, loop_count: u16) {
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let palette = (0..=MAX_LEVEL)
+            .flat_map(|level| {
+                self.display_color
+                    .map(|channel| blend_channel(0x00, channel, level))
+            })
+            .collect::<Vec<_>>();
+
+        let Ok(file) = File::create(path) else {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+            return;
+        };
+
+        let Ok(mut encoder) = gif::Encoder::new(
+            file,
+            width.try_into().unwrap(),
+            height.try_into().unwrap(),
+            &palette,
+        ) else {
+            MessageDialog::new()
+                .set_description(&format!("Could not encode GIF {}", path.display()))
+                .show();
+            return;
+        };
+
+        let repeat = match loop_count {
+            0 => gif::Repeat::Infinite,
+            loop_count => gif::Repeat::Finite(loop_count),
+        };
+        let result = encoder.set_repeat(repeat).and_then(|_| {
+            self.project
+                .image_sequence
+                .iter_frame_durations_ms(self.project.frame_rate)
+                .enumerate()
+                .try_for_each(|(idx, duration)| {
+                    let [left, top, right, bottom] = match idx.checked_sub(1) {
+                        None => [0, 0, width - 1, height - 1],
+                        Some(previous_idx) => self
+                            .project
+                            .image_sequence
+                            .get_changed_bounds(previous_idx, idx)
+                            .unwrap_or([0, 0, 0, 0]),
+                    };
+                    let levels = &self.project.image_sequence.composite_frame_levels(idx);
+                    let buffer = (top..=bottom)
+                        .flat_map(|y| (left..=right).map(move |x| levels[y * width + x]))
+                        .collect::<Vec<_>>();
+                    encoder.write_frame(&gif::Frame {
+                        delay: (duration / 10).max(1).try_into().unwrap_or(u16::MAX),
+                        dispose: gif::DisposalMethod::Keep,
+                        left: left.try_into().unwrap(),
+                        top: top.try_into().unwrap(),
+                        width: (right - left + 1).try_into().unwrap(),
+                        height: (bottom - top + 1).try_into().unwrap(),
+                        buffer: Cow::Owned(buffer),
+                        ..gif::Frame::default()
+                    })
+                })
+        });
+
+        if result.is_err() {
+            MessageDialog::new()
+                .set_description(&format!("Could not encode GIF {}", path.display()))
+                .show();
+        }
+    }
+
+    fn export_css(&self, path: &Path, selector: &str) {
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let color = format!(
+            "#{:02X}{:02X}{:02X}",
+            self.display_color[0], self.display_color[1], self.display_color[2]
+        );
+        let durations = self
+            .project
+            .image_sequence
+            .iter_frame_durations_ms(self.project.frame_rate)
+            .collect::<Vec<_>>();
+        let total_duration: u32 = durations.iter().sum();
+        let offsets = durations
+            .iter()
+            .scan(0, |elapsed, &duration| {
+                let offset = f64::from(*elapsed) / f64::from(total_duration) * 100.0;
+                *elapsed += duration;
+                Some(offset)
+            })
+            .collect::<Vec<_>>();
+        let animation_name = match selector
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect::<String>()
+        {
+            name if name.is_empty() => String::from("pixel"),
+            name => name,
+        };
+
+        let mut css = format!(
+            "{selector} {{\n\
+             \x20   display: grid;\n\
+             \x20   grid-template-columns: repeat({width}, {scale}px);\n\
+             \x20   grid-auto-rows: {scale}px;\n\
+             \x20   width: max-content;\n\
+             }}\n\
+             \n\
+             {selector} > div {{\n\
+             \x20   background-color: #000000;\n\
+             \x20   animation-duration: {total_duration}ms;\n\
+             \x20   animation-iteration-count: infinite;\n\
+             \x20   animation-timing-function: step-end;\n\
+             }}\n",
+            scale = Self::HTML_VIEWER_SCALE
+        );
+        let frames = self
+            .project
+            .image_sequence
+            .iter_frames()
+            .collect::<Vec<_>>();
+        (0..width * height).for_each(|i| {
+            let states = frames
+                .iter()
+                .map(|bitmap| bitmap[i])
+                .collect::<Vec<_>>();
+            if !states.contains(&true) {
+                return;
+            }
+            css += &format!(
+                "\n{selector} > div:nth-child({}) {{\n    animation-name: {animation_name}-{i};\n}}\n\
+                 \n@keyframes {animation_name}-{i} {{\n",
+                i + 1
+            );
+            states
+                .iter()
+                .zip(&offsets)
+                .enumerate()
+                .filter(|&(idx, (state, _))| idx == 0 || states[idx - 1] != *state)
+                .for_each(|(_, (&state, offset))| {
+                    css += &format!(
+                        "    {offset:.3}% {{ background-color: {}; }}\n",
+                        if state { color.as_str() } else { "#000000" }
+                    );
+                });
+            css += &format!(
+                "    100% {{ background-color: {}; }}\n}}\n",
+                if states[states.len() - 1] {
+                    color.as_str()
+                } else {
+                    "#000000"
+                }
+            );
+        });
+
+        let container_attribute = match selector.chars().next() {
+            Some('#') => format!("id=\"{}\"", &selector[1..]),
+            Some('.') => format!("class=\"{}\"", &selector[1..]),
+            _ => format!("class=\"{selector}\""),
+        };
+        let html = format!(
+            "<link rel=\"stylesheet\" href=\"{}\">\n<div {container_attribute}>\n{}</div>\n",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            "    <div></div>\n".repeat(width * height)
+        );
+
+        if fs::write(path, css).is_err() || fs::write(path.with_extension("html"), html).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
+
+    fn export_protobuf(&self, path: &Path) {
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let animation = ProtobufAnimation {
+            width: width.try_into().unwrap(),
+            height: height.try_into().unwrap(),
+            frame_rate: self.project.frame_rate.into(),
+            frames: (0..self.project.image_sequence.get_frame_count())
+                .map(|idx| {
+                    let (dx, dy) = self.project.output_offset;
+                    self.project
+                        .image_sequence
+                        .get_bytes_with_offset(idx, dx, dy)
+                        .collect()
+                })
+                .collect(),
+            frame_durations: self
+                .project
+                .image_sequence
+                .iter_frame_durations_ms(self.project.frame_rate)
+                .collect(),
+            bits_per_pixel: self.bits_per_pixel().into(),
+        };
+
+        if fs::write(path, animation.encode_to_vec()).is_err()
+            || fs::write(path.with_extension("proto"), PROTOBUF_SCHEMA).is_err()
+        {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
+
+    fn export_timing_json(&self, path: &Path) {
+        let metadata = TimingMetadata {
+            frame_rate: self.project.frame_rate,
+            frame_count: self.project.image_sequence.get_frame_count(),
+            durations_ms: self
+                .project
+                .image_sequence
+                .iter_frame_durations_ms(self.project.frame_rate)
+                .collect(),
+        };
+        let serialized = match serde_json::to_string_pretty(&metadata) {
+            Ok(serialized) => serialized,
+            Err(error) => {
+                MessageDialog::new()
+                    .set_description(&format!("Could not serialize timings, error: {error}"))
+                    .show();
+                return;
+            }
+        };
+
+        if fs::write(path, serialized).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
+
+    fn export_msgpack(&self, path: &Path) {
+        let serialized = match rmp_serde::to_vec_named(&self.project) {
+            Ok(serialized) => serialized,
+            Err(error) => {
+                MessageDialog::new()
+                    .set_description(&format!("Could not serialize project, error: {error}"))
+                    .show();
+                return;
+            }
+        };
+
+        if fs::write(path, serialized).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
+
+    fn save_yaml(&self, path: &Path) {
+        let serialized = match serde_yaml::to_string(&self.project) {
+            Ok(serialized) => serialized,
+            Err(error) => {
+                MessageDialog::new()
+                    .set_description(&format!("Could not serialize project, error: {error}"))
+                    .show();
+                return;
+            }
+        };
+
+        if fs::write(path, serialized).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
+
+    fn open_yaml(&mut self, path: &Path) {
+        let Ok(contents) = fs::read_to_string(path) else {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for reading",
+                    path.display()
+                ))
+                .show();
+            return;
+        };
+
+        let Some(project) = serde_yaml::from_str::<Project>(&contents)
+            .ok()
+            .and_then(Project::migrate)
+        else {
+            MessageDialog::new()
+                .set_description(&format!("Could not parse file {}", path.display()))
+                .show();
+            return;
+        };
+
+        self.replace_project(project, None);
+    }
+
+    fn export_xbm(&self, path: &Path) {
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let name = match path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+        {
+            name if name.is_empty() => String::from("image"),
+            name => name,
+        };
+        let xbm = format!(
+            "#define {name}_width {width}\n\
+             #define {name}_height {height}\n\
+             static unsigned char {name}_bits[] = {{\n    {}\n}};\n",
+            self.output_sequence()
+                .get_frame_as_xbm_bytes(self.current_frame - 1)
+                .iter()
+                .map(|byte| format!("{byte:#04x}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        if fs::write(path, xbm).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
     }
 
-    fn import_image(&mut self) {
-        let Some(path) = FileDialog::new()
-            .pick_file() else {
-            return;
-        };
+    fn export_bmp(&self, idx: usize, path: &Path) {
+        if fs::write(path, self.project.image_sequence.get_frame_as_bmp(idx)).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
 
-        let Ok(Ok(image)) = Reader::open(&path).and_then(|reader| reader.with_guessed_format()).map(|reader| reader.decode()) else {
+    fn export_frames_as_images(&self, dir: &Path, format: ImageFormat, scale: u32) {
+        let project_name = self
+            .current_file
+            .as_deref()
+            .and_then(Path::file_stem)
+            .map_or_else(
+                || String::from("untitled"),
+                |stem| stem.to_string_lossy().into_owned(),
+            );
+        let extension = format.extensions_str().first().copied().unwrap_or("img");
+        let frame_count = self.project.image_sequence.get_frame_count();
+        let failed = (0..frame_count)
+            .filter(|&idx| {
+                let image = DynamicImage::ImageRgba8(self.render_frame_image(
+                    &self.project.image_sequence.composite_frame_levels(idx),
+                    self.frame_background(idx),
+                ))
+                .to_rgb8();
+                let image = imageops::resize(
+                    &image,
+                    image.width() * scale,
+                    image.height() * scale,
+                    FilterType::Nearest,
+                );
+                image
+                    .save_with_format(
+                        dir.join(format!("{project_name}_{idx:04}.{extension}")),
+                        format,
+                    )
+                    .is_err()
+            })
+            .count();
+        if failed > 0 {
             MessageDialog::new()
                 .set_description(&format!(
-                    "Could not read/decode {}",
+                    "Could not write {failed} of {frame_count} frames to {}",
+                    dir.display()
+                ))
+                .show();
+        }
+    }
+
+    fn bits_per_pixel(&self) -> u8 {
+        let pixels_per_byte = self.project.image_sequence.get_mode().pixels_per_byte();
+        u8::try_from(8 / pixels_per_byte).unwrap()
+    }
+
+    fn export_html_viewer(&self, path: &Path) {
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let image_sequence = self.output_sequence();
+        let frames = (0..image_sequence.get_frame_count())
+            .map(|idx| {
+                image_sequence
+                    .get_frame_as_string_formatted(idx, ByteFormat::Hex04)
+                    .trim_matches(|c| c == '{' || c == '}')
+                    .to_owned()
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let durations = self
+            .project
+            .image_sequence
+            .iter_frame_durations_ms(self.project.frame_rate)
+            .map(|duration| duration.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let html = HTML_VIEWER_TEMPLATE
+            .replace("$WIDTH$", &width.to_string())
+            .replace("$HEIGHT$", &height.to_string())
+            .replace("$SCALE$", &Self::HTML_VIEWER_SCALE.to_string())
+            .replace("$BITS_PER_PIXEL$", &self.bits_per_pixel().to_string())
+            .replace(
+                "$COLOR$",
+                &format!(
+                    "#{:02X}{:02X}{:02X}",
+                    self.display_color[0], self.display_color[1], self.display_color[2]
+                ),
+            )
+            .replace("$DURATIONS$", &durations)
+            .replace("$FRAMES$", &frames);
+
+        if fs::write(path, html).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
                     path.display()
                 ))
                 .show();
-            return;
+        }
+    }
+
+    fn export_svg(&self, path: &Path) {
+        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
+        let color = format!(
+            "#{:02X}{:02X}{:02X}",
+            self.display_color[0], self.display_color[1], self.display_color[2]
+        );
+        let durations = self
+            .project
+            .image_sequence
+            .iter_frame_durations_ms(self.project.frame_rate)
+            .collect::<Vec<_>>();
+        let total_duration: u32 = durations.iter().sum();
+        let frames = self
+            .project
+            .image_sequence
+            .iter_frame_levels()
+            .collect::<Vec<_>>();
+        let use_references = width * height > Self::SVG_USE_THRESHOLD;
+        let frame_rects = |levels: &[u8]| {
+            levels
+                .iter()
+                .enumerate()
+                .filter(|(_, &level)| level != 0)
+                .map(|(i, &level)| {
+                    let opacity = if level == MAX_LEVEL {
+                        String::new()
+                    } else {
+                        format!(
+                            " fill-opacity=\"{:.3}\"",
+                            f32::from(level) / f32::from(MAX_LEVEL)
+                        )
+                    };
+                    format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\"{opacity}/>",
+                        i % width,
+                        i / width
+                    )
+                })
+                .collect::<String>()
         };
 
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             viewBox=\"0 0 {width} {height}\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n\
+             <rect width=\"{width}\" height=\"{height}\" fill=\"#000000\"/>\n",
+            width * usize::from(Self::HTML_VIEWER_SCALE),
+            height * usize::from(Self::HTML_VIEWER_SCALE)
+        );
+        if use_references {
+            svg += "<defs>\n";
+            frames
+                .iter()
+                .enumerate()
+                .filter(|&(idx, bitmap)| !frames[..idx].contains(bitmap))
+                .for_each(|(idx, bitmap)| {
+                    svg += &format!("<g id=\"frame-{idx}\">{}</g>\n", frame_rects(bitmap));
+                });
+            svg += "</defs>\n";
+        }
+        let mut elapsed = 0;
+        frames
+            .iter()
+            .zip(&durations)
+            .for_each(|(bitmap, &duration)| {
+                let start = f64::from(elapsed) / f64::from(total_duration);
+                elapsed += duration;
+                let end = f64::from(elapsed) / f64::from(total_duration);
+                let content = if use_references {
+                    let source = frames.iter().position(|frame| frame == bitmap).unwrap();
+                    format!("<use xlink:href=\"#frame-{source}\"/>")
+                } else {
+                    frame_rects(bitmap)
+                };
+                svg += &format!(
+                    "<g fill=\"{color}\" visibility=\"hidden\">{content}\
+                     <animate attributeName=\"visibility\" values=\"hidden;visible;hidden\" \
+                     keyTimes=\"0;{start:.4};{end:.4}\" dur=\"{total_duration}ms\" \
+                     calcMode=\"discrete\" repeatCount=\"indefinite\"/></g>\n"
+                );
+            });
+        svg += "</svg>\n";
+
+        if fs::write(path, svg).is_err() {
+            MessageDialog::new()
+                .set_description(&format!(
+                    "Could not open file {} for writing",
+                    path.display()
+                ))
+                .show();
+        }
+    }
+
+    fn frame_background(&self, idx: usize) -> [u8; 3] {
+        self.project
+            .image_sequence
+            .get_background_color(idx)
+            .unwrap_or(Self::BACKGROUND_COLOR)
+    }
+
+    fn render_frame_image(&self, levels: &[u8], background: [u8; 3]) -> RgbaImage {
         let [width, height] = self.project.image_sequence.get_dimensions_pixels();
-        let scaled_image = image.resize_exact(
+        RgbaImage::from_fn(
             width.try_into().unwrap(),
             height.try_into().unwrap(),
-            FilterType::Lanczos3,
+            |x, y| {
+                let level =
+                    levels[usize::try_from(y).unwrap() * width + usize::try_from(x).unwrap()];
+                let [r, g, b] = [0, 1, 2].map(|channel| {
+                    blend_channel(background[channel], self.display_color[channel], level)
+                });
+                Rgba([r, g, b, 0xFF])
+            },
+        )
+    }
+
+    fn copy_frame_to_clipboard(&self, idx: usize) {
+        let image = self.render_frame_image(
+            &self.project.image_sequence.composite_frame_levels(idx),
+            self.frame_background(idx),
+        );
+        let scale = u32::from(self.scale);
+        let image = imageops::resize(
+            &image,
+            image.width() * scale,
+            image.height() * scale,
+            FilterType::Nearest,
         );
-        drop(image);
+        let image_data = ImageData {
+            width: usize::try_from(image.width()).unwrap(),
+            height: usize::try_from(image.height()).unwrap(),
+            bytes: Cow::Owned(image.into_raw()),
+        };
+        if Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_image(image_data))
+            .is_err()
+        {
+            MessageDialog::new()
+                .set_description("Could not copy the frame to the clipboard")
+                .show();
+        }
+    }
 
-        let mut gray_image = scaled_image.into_luma8();
-        imageops::dither(&mut gray_image, &BiLevel);
+    fn render_thumbnail(&self, idx: usize) -> Option<Vec<u8>> {
+        let image = imageops::resize(
+            &self.render_frame_image(
+                &self.project.image_sequence.composite_frame_levels(idx),
+                self.frame_background(idx),
+            ),
+            Self::THUMBNAIL_SIZE,
+            Self::THUMBNAIL_SIZE,
+            FilterType::Nearest,
+        );
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgba8(image)
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .ok()?;
+        Some(bytes)
+    }
 
-        self.project
+    fn export_animation(&self) {
+        let frames = self
+            .project
             .image_sequence
-            .insert_frame(self.current_frame - 1);
-        gray_image
-            .iter()
+            .iter_frame_levels()
             .zip(
                 self.project
                     .image_sequence
-                    .iter_pixels_mut(self.current_frame - 1)
-                    .unwrap(),
+                    .iter_frame_durations_ms(self.project.frame_rate),
             )
-            .for_each(|(&color, pixel)| {
-                *pixel = color != 0;
+            .enumerate()
+            .map(|(idx, (levels, duration))| {
+                image::Frame::from_parts(
+                    self.render_frame_image(&levels, self.frame_background(idx)),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(duration, 1),
+                )
             });
-    }
-
-    fn export_animation(&self) {
-        let [width, height] = self.project.image_sequence.get_dimensions_pixels();
-        let color = [
-            self.display_color[0],
-            self.display_color[1],
-            self.display_color[2],
-            0xFF,
-        ];
-        let frames = self.project.image_sequence.iter_frames().map(|buffer| {
-            let image = RgbaImage::from_fn(
-                width.try_into().unwrap(),
-                height.try_into().unwrap(),
-                |x, y| {
-                    Rgba(
-                        if buffer[usize::try_from(y).unwrap() * width + usize::try_from(x).unwrap()]
-                        {
-                            color
-                        } else {
-                            [0x00, 0x00, 0x00, 0xFF]
-                        },
-                    )
-                },
-            );
-            image::Frame::from_parts(
-                image,
-                0,
-                0,
-                Delay::from_numer_denom_ms(1000, self.project.frame_rate.into()),
-            )
-        });
 
         let Some(path) = FileDialog::new().add_filter("GIF file", &["gif"]).save_file() else {
             return;
@@ -632,3 +4771,645 @@ impl MainWindow {
         }
     }
 }
+
+fn blend_channel(background: u8, color: u8, level: u8) -> u8 {
+    let [background, color, level, max] = [background, color, level, MAX_LEVEL].map(u16::from);
+    u8::try_from((background * (max - level) + color * level) / max).unwrap()
+}
+
+fn rgb_to_hsv([r, g, b]: [u8; 3]) -> [f32; 3] {
+    let [r, g, b] = [r, g, b].map(|channel| f32::from(channel) / 255.0);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue, saturation, max]
+}
+
+fn hsv_to_rgb([hue, saturation, value]: [f32; 3]) -> [u8; 3] {
+    let chroma = value * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let [r, g, b] = match sector as u8 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    let m = value - chroma;
+    [r, g, b].map(|channel| ((channel + m) * 255.0).round() as u8)
+}
+
+fn format_frame_name(pattern: &str, idx: usize, frame_count: usize) -> String {
+    let width = frame_count.to_string().len();
+    pattern
+        .replace("{n}", &(idx + 1).to_string())
+        .replace("{N}", &format!("{:0width$}", idx + 1))
+        .replace("{total}", &frame_count.to_string())
+}
+
+fn onion_opacity_at(opacity: f32, falloff: f32, depth: usize) -> f32 {
+    opacity * (1.0 - falloff).powi(i32::try_from(depth).unwrap_or(i32::MAX))
+}
+
+fn rotate_hue(color: [u8; 3], degrees: f32) -> [u8; 3] {
+    let [hue, saturation, value] = rgb_to_hsv(color);
+    hsv_to_rgb([hue + degrees, saturation, value])
+}
+
+fn read_image(path: &Path) -> Option<DynamicImage> {
+    Reader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .ok()?
+        .decode()
+        .ok()
+}
+
+#[derive(Deserialize)]
+struct ProjectThumbnail {
+    #[serde(default)]
+    thumbnail: Option<Vec<u8>>,
+}
+
+fn decode_project<T: DeserializeOwned>(bytes: &[u8], format: ProjectFormat) -> Option<T> {
+    let decompressed = zstd::decode_all(bytes).ok();
+    let bytes = decompressed.as_deref().unwrap_or(bytes);
+    match format {
+        ProjectFormat::Bson | ProjectFormat::CompressedBson => bson::from_slice(bytes).ok(),
+        ProjectFormat::Cbor => ciborium::de::from_reader(bytes).ok(),
+    }
+}
+
+fn read_project_thumbnail(ctx: &Context, path: &Path) -> Option<TextureHandle> {
+    let file_bytes = fs::read(path).ok()?;
+    let (project_bytes, _) = split_checksum(&file_bytes);
+    let project: ProjectThumbnail = decode_project(project_bytes, ProjectFormat::from_path(path))?;
+    let image = image::load_from_memory(&project.thumbnail?)
+        .ok()?
+        .to_rgba8();
+    let color_image = ColorImage::from_rgba_unmultiplied(
+        [
+            image.width().try_into().unwrap(),
+            image.height().try_into().unwrap(),
+        ],
+        image.as_raw(),
+    );
+    Some(ctx.load_texture(
+        format!("recent_thumbnail_{}", path.display()),
+        color_image,
+        TextureOptions::NEAREST,
+    ))
+}
+
+const MAX_IMPORT_DIMENSION: usize = 4096;
+
+const SYMBOL_FONT_PATHS: &[&str] = &[
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+    "/System/Library/Fonts/Apple Symbols.ttf",
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+];
+
+const EMOJI_RASTER_SIZE: f32 = 128.0;
+
+fn dither_image(
+    image: DynamicImage,
+    width: usize,
+    height: usize,
+    mode: ImportMode,
+) -> Result<GrayImage, String> {
+    if width > MAX_IMPORT_DIMENSION || height > MAX_IMPORT_DIMENSION {
+        return Err(format!(
+            "Canvas size {width}×{height} exceeds the import limit of \
+             {MAX_IMPORT_DIMENSION}×{MAX_IMPORT_DIMENSION}"
+        ));
+    }
+    let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+        return Err(format!("Canvas size {width}×{height} is too large"));
+    };
+
+    let scaled_image = match mode {
+        ImportMode::Stretch => image.resize_exact(width, height, FilterType::Lanczos3),
+        ImportMode::Fit => {
+            let fitted_image = image.resize(width, height, FilterType::Lanczos3);
+            let mut canvas = RgbaImage::from_pixel(width, height, Rgba([0x00, 0x00, 0x00, 0xFF]));
+            imageops::overlay(
+                &mut canvas,
+                &fitted_image.to_rgba8(),
+                i64::from((width - fitted_image.width()) / 2),
+                i64::from((height - fitted_image.height()) / 2),
+            );
+            DynamicImage::ImageRgba8(canvas)
+        }
+        ImportMode::Crop => image.resize_to_fill(width, height, FilterType::Lanczos3),
+    };
+    drop(image);
+
+    let mut gray_image = scaled_image.into_luma8();
+    imageops::dither(&mut gray_image, &BiLevel);
+    Ok(gray_image)
+}
+
+fn write_project(project: &Project, path: &Path) -> bool {
+    let mut serialized = match project.to_bytes(ProjectFormat::from_path(path)) {
+        Ok(serialized) => serialized,
+        Err(error) => {
+            MessageDialog::new()
+                .set_description(&format!("Could not serialize project, error: {error}"))
+                .show();
+            return false;
+        }
+    };
+    append_checksum(&mut serialized);
+
+    if fs::write(path, serialized).is_err() {
+        MessageDialog::new()
+            .set_description(&format!(
+                "Could not open file {} for writing",
+                path.display()
+            ))
+            .show();
+        return false;
+    }
+
+    true
+}
+
+const CHECKSUM_MAGIC: &[u8; 4] = b"CRC ";
+
+fn append_checksum(bytes: &mut Vec<u8>) {
+    let checksum = crc32fast::hash(bytes);
+    bytes.extend_from_slice(CHECKSUM_MAGIC);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+}
+
+fn split_checksum(bytes: &[u8]) -> (&[u8], Option<bool>) {
+    let Some(payload_length) = bytes.len().checked_sub(CHECKSUM_MAGIC.len() + 4) else {
+        return (bytes, None);
+    };
+    let (payload, trailer) = bytes.split_at(payload_length);
+    let (magic, checksum) = trailer.split_at(CHECKSUM_MAGIC.len());
+    if magic != CHECKSUM_MAGIC {
+        return (bytes, None);
+    }
+    let checksum = u32::from_le_bytes(checksum.try_into().unwrap());
+    (payload, Some(crc32fast::hash(payload) == checksum))
+}
+
+fn split_png_stream(mut bytes: &[u8]) -> Vec<&[u8]> {
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    let mut images = Vec::new();
+    while bytes.starts_with(&PNG_SIGNATURE) {
+        let mut end = PNG_SIGNATURE.len();
+        loop {
+            let Some(length) = bytes.get(end..end + 4) else {
+                return images;
+            };
+            let length = u32::from_be_bytes(length.try_into().unwrap()) as usize;
+            let chunk_type = bytes.get(end + 4..end + 8);
+            end += length + 12;
+            if chunk_type == Some(&b"IEND"[..]) {
+                break;
+            }
+        }
+        if end > bytes.len() {
+            break;
+        }
+        let (image, rest) = bytes.split_at(end);
+        images.push(image);
+        bytes = rest;
+    }
+    images
+}
+
+const PROTOBUF_SCHEMA: &str = r#"syntax = "proto3";
+
+// Frames are packed row by row. With 1 bit per pixel a byte holds 8 pixels,
+// most significant bit first; with 4 bits per pixel it holds 2 levels (0-15),
+// high nibble first.
+message Animation {
+  uint32 width = 1;
+  uint32 height = 2;
+  uint32 frame_rate = 3;
+  repeated bytes frames = 4;
+  // Per-frame duration in milliseconds.
+  repeated uint32 frame_durations = 5;
+  // 1 for monochrome, 4 for grayscale.
+  uint32 bits_per_pixel = 6;
+}
+"#;
+
+const HTML_VIEWER_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Animation</title>
+<style>
+body { margin: 0; height: 100vh; display: flex; align-items: center; justify-content: center; background: #202020; }
+</style>
+</head>
+<body>
+<canvas id="viewer"></canvas>
+<script>
+const WIDTH = $WIDTH$;
+const HEIGHT = $HEIGHT$;
+const SCALE = $SCALE$;
+const COLOR = "$COLOR$";
+const BITS_PER_PIXEL = $BITS_PER_PIXEL$;
+const MAX_LEVEL = (1 << BITS_PER_PIXEL) - 1;
+const FRAME_SIZE = Math.ceil(WIDTH * HEIGHT * BITS_PER_PIXEL / 8);
+const DURATIONS = [$DURATIONS$];
+const FRAMES = new Uint8Array([
+$FRAMES$
+]);
+
+const canvas = document.getElementById("viewer");
+canvas.width = WIDTH * SCALE;
+canvas.height = HEIGHT * SCALE;
+const context = canvas.getContext("2d");
+let frame = 0;
+let lastFrameTime = null;
+
+function pixelLevel(i) {
+    const byte = FRAMES[frame * FRAME_SIZE + Math.floor(i * BITS_PER_PIXEL / 8)];
+    if (BITS_PER_PIXEL === 1) {
+        return (byte >> (7 - (i & 7))) & 1;
+    }
+    return i & 1 ? byte & 0x0F : byte >> 4;
+}
+
+function drawFrame() {
+    context.globalAlpha = 1;
+    context.fillStyle = "#000000";
+    context.fillRect(0, 0, canvas.width, canvas.height);
+    context.fillStyle = COLOR;
+    for (let i = 0; i < WIDTH * HEIGHT; i++) {
+        const level = pixelLevel(i);
+        if (level) {
+            context.globalAlpha = level / MAX_LEVEL;
+            context.fillRect((i % WIDTH) * SCALE, Math.floor(i / WIDTH) * SCALE, SCALE, SCALE);
+        }
+    }
+}
+
+function step(time) {
+    if (lastFrameTime === null) {
+        lastFrameTime = time;
+        drawFrame();
+    } else if (time - lastFrameTime >= DURATIONS[frame]) {
+        lastFrameTime = time;
+        frame = (frame + 1) % DURATIONS.length;
+        drawFrame();
+    }
+    requestAnimationFrame(step);
+}
+
+requestAnimationFrame(step);
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bson_round_trip_preserves_pixels() {
+        let mut image_sequence = ImageSequence::new(2, 1);
+        image_sequence.add_frame();
+        image_sequence[[0, 0, 0]] = true;
+        image_sequence[[15, 7, 1]] = true;
+        let project = Project::new(image_sequence, 12);
+
+        let bytes = bson::to_vec(&project).unwrap();
+        let loaded: Project = bson::from_slice(&bytes).unwrap();
+
+        assert_eq!(loaded.frame_rate, 12);
+        assert_eq!(loaded.image_sequence.get_frame_count(), 2);
+        (0..2).for_each(|idx| {
+            assert_eq!(
+                loaded.image_sequence.composite_frame(idx),
+                project.image_sequence.composite_frame(idx)
+            );
+        });
+    }
+
+    #[test]
+    fn yaml_round_trip_uses_hex_bitmaps() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence[[0, 0, 0]] = true;
+        let project = Project::new(image_sequence, 10);
+
+        let yaml = serde_yaml::to_string(&project).unwrap();
+        assert!(yaml.contains("8000000000000000"));
+
+        let loaded = serde_yaml::from_str::<Project>(&yaml)
+            .ok()
+            .and_then(Project::migrate)
+            .unwrap();
+        assert_eq!(
+            loaded.image_sequence.composite_frame(0),
+            project.image_sequence.composite_frame(0)
+        );
+    }
+
+    #[test]
+    fn opening_shorter_project_clamps_current_frame() {
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        let path = std::env::temp_dir().join("maturski_clamp_current_frame.bson");
+        assert!(write_project(&Project::new(image_sequence, 10), &path));
+
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        (0..4).for_each(|_| main_window.project.image_sequence.add_frame());
+        main_window.current_frame = 5;
+        main_window.open_path(path.clone());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(main_window.project.image_sequence.get_frame_count(), 2);
+        assert_eq!(main_window.current_frame, 2);
+    }
+
+    #[test]
+    fn recent_project_thumbnail_is_read_from_file() {
+        let main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        let mut project = Project::new(ImageSequence::new(1, 1), 10);
+        project.thumbnail = main_window.render_thumbnail(0);
+        let path = std::env::temp_dir().join("maturski_recent_thumbnail.bson");
+        assert!(write_project(&project, &path));
+
+        let ctx = Context::default();
+        let texture = read_project_thumbnail(&ctx, &path);
+        project.thumbnail = None;
+        assert!(write_project(&project, &path));
+        let missing = read_project_thumbnail(&ctx, &path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            texture.map(|texture| texture.size()),
+            Some([MainWindow::THUMBNAIL_SIZE as usize; 2])
+        );
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn format_frame_name_expands_placeholders() {
+        assert_eq!(format_frame_name("Frame {n}/{total}", 0, 15), "Frame 1/15");
+        assert_eq!(format_frame_name("{N}", 6, 120), "007");
+        assert_eq!(format_frame_name("static", 3, 4), "static");
+    }
+
+    #[test]
+    fn undo_frame_restores_grayscale_levels() {
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        let image_sequence = &mut main_window.project.image_sequence;
+        image_sequence.set_mode(CanvasMode::Grayscale4Bit);
+        image_sequence.set_level(0, 0, 0, 4);
+        image_sequence.set_level(1, 0, 0, 9);
+        let before = image_sequence.composite_frame_levels(0);
+
+        main_window.invert_frame(0);
+        assert_ne!(
+            main_window.project.image_sequence.composite_frame_levels(0),
+            before
+        );
+        main_window.undo_frame();
+        assert_eq!(
+            main_window.project.image_sequence.composite_frame_levels(0),
+            before
+        );
+    }
+
+    #[test]
+    fn multi_frame_operations_undo_as_one_step() {
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        (0..3).for_each(|_| main_window.project.image_sequence.add_frame());
+        main_window.project.image_sequence[[0, 0, 1]] = true;
+        let before = main_window
+            .project
+            .image_sequence
+            .iter_frames()
+            .collect::<Vec<_>>();
+        main_window.selected_frames.extend([1, 3]);
+        main_window.apply_to_target_frames(ImageSequence::invert_frame);
+        main_window.invert_frame(3);
+        main_window.current_frame = 4;
+        main_window.undo_frame();
+        assert!(!main_window.project.image_sequence[[0, 0, 1]]);
+        assert!(main_window.project.image_sequence[[0, 0, 3]]);
+
+        main_window.undo_frame();
+        assert_eq!(
+            main_window
+                .project
+                .image_sequence
+                .iter_frames()
+                .collect::<Vec<_>>(),
+            before
+        );
+    }
+
+    #[test]
+    fn replacing_project_resets_saved_state() {
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        (0..3).for_each(|_| main_window.project.image_sequence.add_frame());
+        main_window.current_frame = 4;
+        main_window.push_frame_history(0);
+
+        let mut image_sequence = ImageSequence::new(1, 1);
+        image_sequence.add_frame();
+        image_sequence[[0, 0, 1]] = true;
+        main_window.replace_project(Project::new(image_sequence, 10), None);
+
+        assert_eq!(main_window.current_frame, 2);
+        assert!(main_window.frame_histories.is_empty());
+        assert_eq!(
+            main_window.last_saved_bitmaps,
+            main_window
+                .project
+                .image_sequence
+                .iter_frames()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn grow_and_shrink_change_only_the_selection() {
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        main_window.project.image_sequence[[3, 3, 0]] = true;
+        let bounds = |main_window: &MainWindow| {
+            main_window
+                .selection
+                .map(|selection| (selection.x, selection.y, selection.width, selection.height))
+        };
+
+        main_window.morph_selection(dilate_bitmap);
+        assert_eq!(bounds(&main_window), None);
+
+        main_window.selection = Some(Selection {
+            x: 0,
+            y: 3,
+            width: 3,
+            height: 4,
+        });
+        main_window.morph_selection(dilate_bitmap);
+        assert_eq!(bounds(&main_window), Some((0, 2, 4, 6)));
+        main_window.morph_selection(erode_bitmap);
+        main_window.morph_selection(erode_bitmap);
+        assert_eq!(bounds(&main_window), Some((0, 4, 2, 2)));
+        main_window.morph_selection(erode_bitmap);
+        assert_eq!(bounds(&main_window), None);
+
+        assert_eq!(main_window.project.image_sequence.frame_stats(0).lit, 1);
+        assert!(main_window.frame_histories.is_empty());
+    }
+
+    #[test]
+    fn code_template_expands_placeholders() {
+        let (_, template) = &CodeTemplate::presets()[2];
+        let frames = vec![
+            vec![String::from("0x01"), String::from("0x02")],
+            vec![String::from("0x03"), String::from("0x04")],
+        ];
+
+        assert_eq!(
+            template.render("IMAGE", &frames),
+            "pub const IMAGE: [[u8; 2]; 2] = [\n    [0x01, 0x02],\n    [0x03, 0x04]\n];\n"
+        );
+    }
+
+    #[test]
+    fn onion_opacity_falls_off_with_depth() {
+        assert_eq!(onion_opacity_at(0.4, 0.0, 3), 0.4);
+        assert_eq!(onion_opacity_at(0.4, 0.5, 1), 0.2);
+        assert_eq!(onion_opacity_at(0.4, 0.5, 2), 0.1);
+        assert_eq!(onion_opacity_at(0.4, 1.0, 1), 0.0);
+    }
+
+    #[test]
+    fn html_viewer_decodes_grayscale_nibbles() {
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        assert_eq!(main_window.bits_per_pixel(), 1);
+        main_window
+            .project
+            .image_sequence
+            .set_mode(CanvasMode::Grayscale4Bit);
+        assert_eq!(main_window.bits_per_pixel(), 4);
+
+        let path = std::env::temp_dir().join("maturski_grayscale_viewer.html");
+        main_window.export_html_viewer(&path);
+        let html = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(html.contains("const BITS_PER_PIXEL = 4;"));
+        assert!(!html.contains('$'));
+    }
+
+    #[test]
+    fn html_viewer_applies_output_offset() {
+        let mut main_window =
+            MainWindow::new(DefaultProjectSettings::default(), Vec::new(), Vec::new());
+        main_window.project.image_sequence[[0, 0, 0]] = true;
+        main_window.project.output_offset = (1, 0);
+
+        let path = std::env::temp_dir().join("maturski_offset_viewer.html");
+        main_window.export_html_viewer(&path);
+        let html = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(html.contains("const FRAMES = new Uint8Array([\n0x40, 0x00"));
+    }
+
+    #[test]
+    fn blend_channel_scales_between_background_and_color() {
+        assert_eq!(blend_channel(0x00, 0xFF, 0), 0x00);
+        assert_eq!(blend_channel(0x00, 0xFF, MAX_LEVEL), 0xFF);
+        assert_eq!(blend_channel(0x00, 0xFF, 5), 0x55);
+        assert_eq!(blend_channel(0xFF, 0x00, 10), 0x55);
+    }
+
+    #[test]
+    fn rotate_hue_shifts_primaries() {
+        assert_eq!(rotate_hue([0xFF, 0x00, 0x00], 0.0), [0xFF, 0x00, 0x00]);
+        assert_eq!(rotate_hue([0xFF, 0x00, 0x00], 120.0), [0x00, 0xFF, 0x00]);
+        assert_eq!(rotate_hue([0xFF, 0x00, 0x00], 240.0), [0x00, 0x00, 0xFF]);
+        assert_eq!(rotate_hue([0x80, 0x80, 0x80], 30.0), [0x80, 0x80, 0x80]);
+    }
+
+    #[test]
+    fn old_bson_files_load_with_defaults() {
+        let legacy_bitmaps = bson::to_bson(&vec![vec![true; 64], vec![false; 64]]).unwrap();
+        let legacy = bson::doc! {
+            "image_sequence": {
+                "bitmaps": legacy_bitmaps,
+                "width": 1,
+                "height": 1,
+            },
+            "frame_rate": 10,
+        };
+        let project =
+            Project::from_bytes(&bson::to_vec(&legacy).unwrap(), ProjectFormat::Bson).unwrap();
+        assert_eq!(project.version, Project::VERSION);
+        assert_eq!(project.image_sequence.get_frame_count(), 2);
+        assert_eq!(project.image_sequence.get_layer_count(), 1);
+        assert_eq!(project.image_sequence.get_frame_duration(1), Some(0));
+        assert!(project
+            .image_sequence
+            .composite_frame(0)
+            .iter()
+            .all(|&pixel| pixel));
+
+        let without_layer_names = bson::doc! {
+            "image_sequence": {
+                "frames": [{ "layers": [bson::Binary {
+                    subtype: bson::spec::BinarySubtype::Generic,
+                    bytes: vec![0xFF; 8],
+                }] }],
+                "width": 1,
+                "height": 1,
+            },
+            "frame_rate": 10,
+        };
+        let project = Project::from_bytes(
+            &bson::to_vec(&without_layer_names).unwrap(),
+            ProjectFormat::Bson,
+        )
+        .unwrap();
+        assert_eq!(project.image_sequence.get_layer_name(0), "Layer 1");
+        assert!(project.image_sequence.is_layer_enabled(0, 0));
+        assert!(project
+            .image_sequence
+            .composite_frame(0)
+            .iter()
+            .all(|&pixel| pixel));
+    }
+
+    #[test]
+    fn corrupted_bson_is_rejected() {
+        let mut bytes = bson::to_vec(&Project::new(ImageSequence::new(1, 1), 10)).unwrap();
+        bytes.truncate(bytes.len() / 2);
+
+        assert!(bson::from_slice::<Project>(&bytes).is_err());
+        assert!(Project::from_bytes(&bytes, ProjectFormat::Bson).is_none());
+    }
+}
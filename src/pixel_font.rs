@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const COMPACT_4X8_FIRST_CHARACTER: u8 = 32;
+
+// One row per nibble, most significant bit is the leftmost column.
+const COMPACT_4X8_GLYPHS: [[u8; 8]; 96] = [
+    [0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0x4, 0x4, 0x4, 0x0, 0x4, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0x0, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0xA, 0xE, 0xA, 0xE, 0xA, 0x0, 0x0],
+    [0x0, 0x6, 0xC, 0x4, 0x6, 0xC, 0x0, 0x0],
+    [0x0, 0x8, 0x2, 0x4, 0x8, 0x2, 0x0, 0x0],
+    [0x0, 0x4, 0xA, 0x4, 0xA, 0x6, 0x0, 0x0],
+    [0x0, 0x4, 0x4, 0x0, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0x2, 0x4, 0x4, 0x4, 0x2, 0x0, 0x0],
+    [0x0, 0x8, 0x4, 0x4, 0x4, 0x8, 0x0, 0x0],
+    [0x0, 0x0, 0xA, 0x4, 0xA, 0x0, 0x0, 0x0],
+    [0x0, 0x0, 0x4, 0xE, 0x4, 0x0, 0x0, 0x0],
+    [0x0, 0x0, 0x0, 0x0, 0x4, 0x8, 0x0, 0x0],
+    [0x0, 0x0, 0x0, 0xE, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0x0, 0x0, 0x0, 0x0, 0x4, 0x0, 0x0],
+    [0x0, 0x2, 0x2, 0x4, 0x8, 0x8, 0x0, 0x0],
+    [0x0, 0xE, 0xA, 0xA, 0xA, 0xE, 0x0, 0x0],
+    [0x0, 0x4, 0xC, 0x4, 0x4, 0xE, 0x0, 0x0],
+    [0x0, 0xC, 0x2, 0x4, 0x8, 0xE, 0x0, 0x0],
+    [0x0, 0xC, 0x2, 0x4, 0x2, 0xC, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0xE, 0x2, 0x2, 0x0, 0x0],
+    [0x0, 0xE, 0x8, 0xC, 0x2, 0xC, 0x0, 0x0],
+    [0x0, 0x6, 0x8, 0xE, 0xA, 0xE, 0x0, 0x0],
+    [0x0, 0xE, 0x2, 0x4, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0xE, 0xA, 0xE, 0xA, 0xE, 0x0, 0x0],
+    [0x0, 0xE, 0xA, 0xE, 0x2, 0xC, 0x0, 0x0],
+    [0x0, 0x0, 0x4, 0x0, 0x4, 0x0, 0x0, 0x0],
+    [0x0, 0x0, 0x4, 0x0, 0x4, 0x8, 0x0, 0x0],
+    [0x0, 0x2, 0x4, 0x8, 0x4, 0x2, 0x0, 0x0],
+    [0x0, 0x0, 0xE, 0x0, 0xE, 0x0, 0x0, 0x0],
+    [0x0, 0x8, 0x4, 0x2, 0x4, 0x8, 0x0, 0x0],
+    [0x0, 0xC, 0x2, 0x4, 0x0, 0x4, 0x0, 0x0],
+    [0x0, 0x4, 0xA, 0xE, 0x8, 0x6, 0x0, 0x0],
+    [0x0, 0x4, 0xA, 0xE, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0xC, 0xA, 0xC, 0xA, 0xC, 0x0, 0x0],
+    [0x0, 0x6, 0x8, 0x8, 0x8, 0x6, 0x0, 0x0],
+    [0x0, 0xC, 0xA, 0xA, 0xA, 0xC, 0x0, 0x0],
+    [0x0, 0xE, 0x8, 0xE, 0x8, 0xE, 0x0, 0x0],
+    [0x0, 0xE, 0x8, 0xE, 0x8, 0x8, 0x0, 0x0],
+    [0x0, 0x6, 0x8, 0xA, 0xA, 0x6, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0xE, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0xE, 0x4, 0x4, 0x4, 0xE, 0x0, 0x0],
+    [0x0, 0x2, 0x2, 0x2, 0xA, 0x4, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0xC, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0x8, 0x8, 0x8, 0x8, 0xE, 0x0, 0x0],
+    [0x0, 0xA, 0xE, 0xE, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0xA, 0xE, 0xE, 0xE, 0xA, 0x0, 0x0],
+    [0x0, 0x4, 0xA, 0xA, 0xA, 0x4, 0x0, 0x0],
+    [0x0, 0xC, 0xA, 0xC, 0x8, 0x8, 0x0, 0x0],
+    [0x0, 0x4, 0xA, 0xA, 0xE, 0x6, 0x0, 0x0],
+    [0x0, 0xC, 0xA, 0xE, 0xC, 0xA, 0x0, 0x0],
+    [0x0, 0x6, 0x8, 0x4, 0x2, 0xC, 0x0, 0x0],
+    [0x0, 0xE, 0x4, 0x4, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0xA, 0xA, 0x6, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0xA, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0xE, 0xE, 0xA, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0x4, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0xA, 0xA, 0x4, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0xE, 0x2, 0x4, 0x8, 0xE, 0x0, 0x0],
+    [0x0, 0xC, 0x8, 0x8, 0x8, 0xC, 0x0, 0x0],
+    [0x0, 0x8, 0x8, 0x4, 0x2, 0x2, 0x0, 0x0],
+    [0x0, 0x6, 0x2, 0x2, 0x2, 0x6, 0x0, 0x0],
+    [0x0, 0x4, 0xA, 0x0, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0x0, 0x0, 0x0, 0x0, 0xE, 0x0, 0x0],
+    [0x0, 0x8, 0x4, 0x0, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0x0, 0xC, 0x6, 0xA, 0xE, 0x0, 0x0],
+    [0x0, 0x8, 0xC, 0xA, 0xA, 0xC, 0x0, 0x0],
+    [0x0, 0x0, 0x6, 0x8, 0x8, 0x6, 0x0, 0x0],
+    [0x0, 0x2, 0x6, 0xA, 0xA, 0x6, 0x0, 0x0],
+    [0x0, 0x0, 0x6, 0xA, 0xC, 0x6, 0x0, 0x0],
+    [0x0, 0x2, 0x4, 0xE, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0x0, 0x6, 0xA, 0x6, 0x2, 0xC, 0x0],
+    [0x0, 0x8, 0xC, 0xA, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0x4, 0x0, 0x4, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0x2, 0x0, 0x2, 0x2, 0xA, 0x4, 0x0],
+    [0x0, 0x8, 0xA, 0xC, 0xC, 0xA, 0x0, 0x0],
+    [0x0, 0xC, 0x4, 0x4, 0x4, 0xE, 0x0, 0x0],
+    [0x0, 0x0, 0xE, 0xE, 0xE, 0xA, 0x0, 0x0],
+    [0x0, 0x0, 0xC, 0xA, 0xA, 0xA, 0x0, 0x0],
+    [0x0, 0x0, 0x4, 0xA, 0xA, 0x4, 0x0, 0x0],
+    [0x0, 0x0, 0xC, 0xA, 0xA, 0xC, 0x8, 0x0],
+    [0x0, 0x0, 0x6, 0xA, 0xA, 0x6, 0x2, 0x0],
+    [0x0, 0x0, 0x6, 0x8, 0x8, 0x8, 0x0, 0x0],
+    [0x0, 0x0, 0x6, 0xC, 0x6, 0xC, 0x0, 0x0],
+    [0x0, 0x4, 0xE, 0x4, 0x4, 0x6, 0x0, 0x0],
+    [0x0, 0x0, 0xA, 0xA, 0xA, 0x6, 0x0, 0x0],
+    [0x0, 0x0, 0xA, 0xA, 0xE, 0x4, 0x0, 0x0],
+    [0x0, 0x0, 0xA, 0xE, 0xE, 0xE, 0x0, 0x0],
+    [0x0, 0x0, 0xA, 0x4, 0x4, 0xA, 0x0, 0x0],
+    [0x0, 0x0, 0xA, 0xA, 0x6, 0x2, 0xC, 0x0],
+    [0x0, 0x0, 0xE, 0x6, 0xC, 0xE, 0x0, 0x0],
+    [0x0, 0x6, 0x4, 0xC, 0x4, 0x6, 0x0, 0x0],
+    [0x0, 0x4, 0x4, 0x4, 0x4, 0x4, 0x0, 0x0],
+    [0x0, 0xC, 0x4, 0x6, 0x4, 0xC, 0x0, 0x0],
+    [0x0, 0x0, 0x6, 0xC, 0x0, 0x0, 0x0, 0x0],
+    [0x0, 0xE, 0xE, 0xE, 0xE, 0xE, 0x0, 0x0],
+];
+
+pub struct PixelFont {
+    width: usize,
+    height: usize,
+    glyphs: HashMap<char, Vec<bool>>,
+}
+
+#[derive(Debug)]
+pub enum BdfError {
+    Io(io::Error),
+    MissingBoundingBox,
+    InvalidBoundingBox(usize),
+    InvalidLine(usize),
+}
+
+impl Display for BdfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BdfError::Io(error) => write!(f, "{error}"),
+            BdfError::MissingBoundingBox => write!(f, "missing FONTBOUNDINGBOX"),
+            BdfError::InvalidBoundingBox(line_number) => {
+                write!(f, "invalid bounding box on line {line_number}")
+            }
+            BdfError::InvalidLine(line_number) => write!(f, "invalid line {line_number}"),
+        }
+    }
+}
+
+impl PixelFont {
+    pub fn from_bdf(path: &Path) -> Result<Self, BdfError> {
+        Self::parse_bdf(&fs::read_to_string(path).map_err(BdfError::Io)?)
+    }
+
+    pub fn compact_4x8() -> Self {
+        Self {
+            width: 4,
+            height: 8,
+            glyphs: COMPACT_4X8_GLYPHS
+                .iter()
+                .zip(COMPACT_4X8_FIRST_CHARACTER..)
+                .map(|(rows, code)| {
+                    (
+                        char::from(code),
+                        compact_glyph(rows).into_iter().flatten().collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn parse_bdf(contents: &str) -> Result<Self, BdfError> {
+        let mut font_bounding_box: Option<[i32; 4]> = None;
+        let mut font_size: Option<[usize; 2]> = None;
+        let mut glyphs = HashMap::new();
+        let mut encoding = None;
+        let mut glyph_bounding_box: Option<[i32; 4]> = None;
+        let mut bitmap_rows: Option<Vec<(u64, usize)>> = None;
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let invalid_line = || BdfError::InvalidLine(line_number + 1);
+            let line = line.trim();
+
+            if let Some(rows) = &mut bitmap_rows {
+                if line != "ENDCHAR" {
+                    rows.push((
+                        u64::from_str_radix(line, 16).map_err(|_| invalid_line())?,
+                        line.len() * 4,
+                    ));
+                    continue;
+                }
+
+                let (Some([font_width, font_height, font_x, font_y]), Some([width, height])) =
+                    (font_bounding_box, font_size)
+                else {
+                    return Err(BdfError::MissingBoundingBox);
+                };
+                let [glyph_width, glyph_height, glyph_x, glyph_y] =
+                    glyph_bounding_box.ok_or_else(invalid_line)?;
+                let mut pixels = vec![false; width * height];
+                rows.iter()
+                    .enumerate()
+                    .for_each(|(row, &(bits, bit_count))| {
+                        let y = font_y + font_height - glyph_y - glyph_height + row as i32;
+                        (0..glyph_width.min(bit_count as i32)).for_each(|column| {
+                            let x = glyph_x - font_x + column;
+                            if (0..font_width).contains(&x)
+                                && (0..font_height).contains(&y)
+                                && (bits >> (bit_count as i32 - 1 - column)) & 1 == 1
+                            {
+                                pixels[usize::try_from(y * font_width + x).unwrap()] = true;
+                            }
+                        });
+                    });
+                if let Some(character) = encoding.and_then(char::from_u32) {
+                    glyphs.insert(character, pixels);
+                }
+
+                bitmap_rows = None;
+                encoding = None;
+                glyph_bounding_box = None;
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("FONTBOUNDINGBOX") => {
+                    let bounding_box = parse_bounding_box(tokens).ok_or_else(invalid_line)?;
+                    let [Some(width), Some(height)] = [bounding_box[0], bounding_box[1]]
+                        .map(|size| usize::try_from(size).ok().filter(|&size| size > 0))
+                    else {
+                        return Err(BdfError::InvalidBoundingBox(line_number + 1));
+                    };
+                    font_bounding_box = Some(bounding_box);
+                    font_size = Some([width, height]);
+                }
+                Some("ENCODING") => {
+                    encoding = tokens.next().and_then(|token| token.parse().ok());
+                }
+                Some("BBX") => {
+                    let bounding_box = parse_bounding_box(tokens).ok_or_else(invalid_line)?;
+                    if bounding_box[0] < 0 || bounding_box[1] < 0 {
+                        return Err(BdfError::InvalidBoundingBox(line_number + 1));
+                    }
+                    glyph_bounding_box = Some(bounding_box);
+                }
+                Some("BITMAP") => bitmap_rows = Some(Vec::new()),
+                _ => {}
+            }
+        }
+
+        let [width, height] = font_size.ok_or(BdfError::MissingBoundingBox)?;
+        Ok(Self {
+            width,
+            height,
+            glyphs,
+        })
+    }
+
+    pub fn render(&self, text: &str) -> (Vec<bool>, usize) {
+        let characters = text.chars().collect::<Vec<_>>();
+        let width = characters.len() * self.width;
+        let mut pixels = vec![false; width * self.height];
+        characters.iter().enumerate().for_each(|(i, character)| {
+            if let Some(glyph) = self.glyphs.get(character) {
+                glyph.iter().enumerate().for_each(|(j, &pixel)| {
+                    pixels[j / self.width * width + i * self.width + j % self.width] = pixel;
+                });
+            }
+        });
+        (pixels, width)
+    }
+}
+
+fn compact_glyph(rows: &[u8; 8]) -> [[bool; 4]; 8] {
+    rows.map(|row| [0, 1, 2, 3].map(|column| (row >> (3 - column)) & 1 == 1))
+}
+
+fn parse_bounding_box<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Option<[i32; 4]> {
+    let mut next = || tokens.next()?.parse().ok();
+    Some([next()?, next()?, next()?, next()?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_bounding_boxes_are_rejected() {
+        assert!(matches!(
+            PixelFont::parse_bdf("STARTFONT 2.1\nFONTBOUNDINGBOX -5 8 0 0\nENDFONT\n"),
+            Err(BdfError::InvalidBoundingBox(2))
+        ));
+        assert!(matches!(
+            PixelFont::parse_bdf(
+                "FONTBOUNDINGBOX 4 8 0 0\nSTARTCHAR A\nENCODING 65\nBBX 4 -8 0 0\nBITMAP\nENDCHAR\n"
+            ),
+            Err(BdfError::InvalidBoundingBox(4))
+        ));
+    }
+
+    #[test]
+    fn parses_a_minimal_glyph() {
+        let font = PixelFont::parse_bdf(
+            "FONTBOUNDINGBOX 2 2 0 0\nSTARTCHAR A\nENCODING 65\nBBX 2 2 0 0\nBITMAP\n80\n40\nENDCHAR\n",
+        )
+        .unwrap();
+        assert_eq!(font.render("A"), (vec![true, false, false, true], 2));
+    }
+}